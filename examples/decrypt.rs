@@ -0,0 +1,29 @@
+//! Reads codes from stdin, one per line, and prints them auto-decrypted.
+//!
+//! ```text
+//! $ printf '2AFF014C 2411FFFF\nB4336FA9 4DFEFB79\n973E0B2A A7D4AF10\n' | cargo run --example decrypt
+//! 2043AFCC 2411FFFF
+//! BEEFC0DE 00000000
+//! 2096F5B8 000000BE
+//! ```
+//!
+//! Blank lines and lines that are not codes, like cheat titles, are printed
+//! unchanged.
+
+use codebreaker::{Code, Codebreaker};
+use std::io::{self, BufRead, Write};
+
+fn main() -> io::Result<()> {
+    let mut cb = Codebreaker::new();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        match line.parse::<Code>() {
+            Ok(code) => writeln!(out, "{}", cb.auto_decrypt(code))?,
+            Err(_) => writeln!(out, "{line}")?,
+        }
+    }
+    Ok(())
+}
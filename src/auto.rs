@@ -0,0 +1,218 @@
+//! The state machine behind auto-decryption.
+
+use crate::cb7::{is_beefcode, Cb7};
+use crate::{cb1, is_headerless_v7, is_known_command, is_noop_line, looks_v1_encrypted, num_code_lines, Code, Scheme};
+
+/// The state machine that detects if and how each code of a list needs to be
+/// decrypted, as used by [`Codebreaker::auto_decrypt_code`].
+///
+/// It keeps track of the current encryption scheme, the v7 seeds, and the
+/// number of lines left of the current multi-line command. Driving it
+/// directly allows inspecting every transition.
+///
+/// [`Codebreaker::auto_decrypt_code`]: crate::Codebreaker::auto_decrypt_code
+///
+/// # Example
+/// ```
+/// use codebreaker::{AutoDecryptState, Code, Scheme};
+///
+/// let mut state = AutoDecryptState::new();
+/// let out = state.step(Code(0x9A545CC6, 0x188CBCFB));
+/// assert_eq!(out.code, Code(0x9029BEAC, 0x0C0A9225));
+/// assert_eq!(out.scheme, Scheme::V1);
+/// assert!(out.scheme_changed);
+///
+/// let out = state.step(Code(0xB4336FA9, 0x4DFEFB79));
+/// assert_eq!(out.code, Code(0xBEEFC0DE, 0x00000000));
+/// assert_eq!(out.scheme, Scheme::V7);
+/// assert_eq!(state.lines_remaining(), 1);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AutoDecryptState {
+    pub(crate) scheme: Scheme,
+    pub(crate) cb7: Cb7,
+    pub(crate) code_lines: usize,
+    pub(crate) passthrough_unknown: bool,
+    pub(crate) detect_headerless_v7: bool,
+    pub(crate) started: bool,
+}
+
+/// The result of [`AutoDecryptState::step`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoDecryptOutput {
+    /// The decrypted code.
+    pub code: Code,
+    /// The scheme after processing the code.
+    pub scheme: Scheme,
+    /// Whether processing the code changed the scheme.
+    pub scheme_changed: bool,
+}
+
+/// Does the same as [`new`](#method.new).
+impl Default for AutoDecryptState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AutoDecryptState {
+    /// Returns a new state machine for the start of a list.
+    pub const fn new() -> Self {
+        Self::with_cb7(Scheme::Raw, Cb7::new())
+    }
+
+    pub(crate) const fn with_cb7(scheme: Scheme, cb7: Cb7) -> Self {
+        Self {
+            scheme,
+            cb7,
+            code_lines: 0,
+            passthrough_unknown: false,
+            detect_headerless_v7: false,
+            started: false,
+        }
+    }
+
+    /// Passes through codes with an unknown command unchanged instead of
+    /// decrypting them.
+    ///
+    /// See [`Codebreaker::passthrough_unknown`](crate::Codebreaker::passthrough_unknown).
+    pub const fn passthrough_unknown(mut self, enabled: bool) -> Self {
+        self.passthrough_unknown = enabled;
+        self
+    }
+
+    /// Decrypts a list as v7 if its first code looks like a CMGSCCC code
+    /// without header.
+    ///
+    /// See [`Codebreaker::detect_headerless_v7`](crate::Codebreaker::detect_headerless_v7).
+    pub const fn detect_headerless_v7(mut self, enabled: bool) -> Self {
+        self.detect_headerless_v7 = enabled;
+        self
+    }
+
+    /// Returns the current encryption scheme.
+    pub const fn scheme(&self) -> Scheme {
+        self.scheme
+    }
+
+    /// Returns the number of lines still expected for the current multi-line
+    /// command.
+    pub const fn lines_remaining(&self) -> usize {
+        self.code_lines
+    }
+
+    /// Processes the next code of a list and returns it decrypted, together
+    /// with the resulting scheme.
+    pub fn step(&mut self, code: Code) -> AutoDecryptOutput {
+        let before = self.scheme;
+        let (mut addr, mut val) = code.as_tuple();
+        self.step_mut(&mut addr, &mut val);
+        AutoDecryptOutput {
+            code: Code(addr, val),
+            scheme: self.scheme,
+            scheme_changed: self.scheme != before,
+        }
+    }
+
+    pub(crate) fn step_mut(&mut self, addr: &mut u32, val: &mut u32) {
+        // Only outside of v7, where the line is not ciphertext
+        if self.scheme != Scheme::V7 && self.code_lines == 0 && is_noop_line(*addr, *val) {
+            return;
+        }
+
+        if !self.started {
+            self.started = true;
+            let unknown = self.passthrough_unknown && !is_known_command(*addr);
+            if self.detect_headerless_v7 && self.scheme == Scheme::Raw && !unknown && is_headerless_v7(*addr, *val) {
+                self.set_scheme(Scheme::V7);
+                self.cb7 = Cb7::default();
+            }
+        }
+
+        if self.scheme != Scheme::V7 {
+            if self.code_lines == 0 {
+                // The command nibble survives v1 encryption
+                if self.passthrough_unknown && !is_known_command(*addr) {
+                    return;
+                }
+                if looks_v1_encrypted(*addr) {
+                    if is_beefcode(*addr) {
+                        // ignore raw beefcode
+                        return;
+                    }
+                    self.set_scheme(Scheme::V1);
+                    cb1::decrypt_code_mut(addr, val);
+                } else {
+                    self.set_scheme(Scheme::Raw);
+                }
+                // Only the command nibble survives v1 encryption, so count
+                // lines on the decrypted code
+                self.code_lines = num_code_lines(*addr) - 1;
+            } else {
+                self.code_lines -= 1;
+                if self.scheme == Scheme::Raw {
+                    return;
+                }
+                cb1::decrypt_code_mut(addr, val);
+            }
+        } else {
+            let (oldaddr, oldval) = (*addr, *val);
+            self.cb7.decrypt_code_mut(addr, val);
+            if self.code_lines == 0 {
+                if self.passthrough_unknown && !is_known_command(*addr) {
+                    *addr = oldaddr;
+                    *val = oldval;
+                    return;
+                }
+                self.code_lines = num_code_lines(*addr);
+                if self.code_lines == 1 && *addr == 0xffff_ffff {
+                    // XXX: changing encryption via "FFFFFFFF 000xnnnn" is not supported
+                    self.code_lines = 0;
+                    return;
+                }
+            }
+            self.code_lines -= 1;
+        }
+
+        if is_beefcode(*addr) {
+            self.cb7.beefcode(*addr, *val);
+            self.set_scheme(Scheme::V7);
+            self.code_lines = 1;
+        }
+    }
+
+    pub(crate) fn set_scheme(&mut self, scheme: Scheme) {
+        if self.scheme != scheme {
+            trace_event!(from = ?self.scheme, to = ?scheme, "scheme changed");
+            self.scheme = scheme;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "std")]
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_step() {
+        let tests: &[(&str, &str, Scheme, bool, usize)] = &[
+            ("2043AFCC 2411FFFF", "2043AFCC 2411FFFF", Scheme::Raw, false, 0),
+            ("4A0668F5 C4370BF1", "4031789A 00040001", Scheme::V1, true, 1),
+            ("0A80080D 00000000", "00000063 00000000", Scheme::V1, false, 0),
+            ("2A973DBD 00000000", "201F6024 00000000", Scheme::V1, false, 0),
+            ("B4336FA9 4DFEFB79", "BEEFC0DE 00000000", Scheme::V7, true, 1),
+            ("973E0B2A A7D4AF10", "2096F5B8 000000BE", Scheme::V7, false, 0),
+        ];
+        let mut state = AutoDecryptState::new();
+        for &(input, output, scheme, changed, lines) in tests {
+            let out = state.step(input.into());
+            assert_eq!(out.code, output.into());
+            assert_eq!(out.scheme, scheme);
+            assert_eq!(out.scheme_changed, changed);
+            assert_eq!(state.scheme(), scheme);
+            assert_eq!(state.lines_remaining(), lines);
+        }
+    }
+}
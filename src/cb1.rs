@@ -1,5 +1,22 @@
 //! Encrypt and decrypt cheat codes for CodeBreaker PS2 v1 - v6.
 
+/// Returns the command of a code, which is the upper nibble of the address
+/// in the range 0 - 15.
+///
+/// The command is not changed by encryption.
+///
+/// # Example
+/// ```
+/// use codebreaker::cb1;
+///
+/// assert_eq!(cb1::command(0x1023CED8), 0x1);
+/// assert_eq!(cb1::command(0x1A11330E), 0x1);
+/// assert_eq!(cb1::command(0xBEEFC0DE), 0xB);
+/// ```
+pub const fn command(addr: u32) -> u8 {
+    (addr >> 28) as u8
+}
+
 /// Encrypts a code and returns the result.
 ///
 /// # Example
@@ -10,7 +27,7 @@
 /// assert_eq!(code, (0x1A11330E, 0x000003E7));
 /// ```
 pub const fn encrypt_code(mut addr: u32, mut val: u32) -> (u32, u32) {
-    let cmd = (addr >> 28) as usize;
+    let cmd = command(addr) as usize;
     let tmp = addr & 0xff00_0000;
     addr = ((addr & 0xff) << 16) | ((addr >> 8) & 0xffff);
     addr = (tmp | (addr.wrapping_add(SEEDS[1][cmd]) & 0x00ff_ffff)) ^ SEEDS[0][cmd];
@@ -30,7 +47,7 @@ pub const fn encrypt_code(mut addr: u32, mut val: u32) -> (u32, u32) {
 /// cb1::encrypt_code_mut(&mut code.0, &mut code.1);
 /// assert_eq!(code, (0x1A11330E, 0x000003E7));
 /// ```
-pub fn encrypt_code_mut(addr: &mut u32, val: &mut u32) {
+pub const fn encrypt_code_mut(addr: &mut u32, val: &mut u32) {
     let code = encrypt_code(*addr, *val);
     *addr = code.0;
     *val = code.1;
@@ -46,7 +63,7 @@ pub fn encrypt_code_mut(addr: &mut u32, val: &mut u32) {
 /// assert_eq!(code, (0x1023CED8, 0x000003E7));
 /// ```
 pub const fn decrypt_code(mut addr: u32, mut val: u32) -> (u32, u32) {
-    let cmd = (addr >> 28) as usize;
+    let cmd = command(addr) as usize;
     if cmd > 2 {
         val = (addr ^ val).wrapping_sub(SEEDS[2][cmd]);
     }
@@ -66,12 +83,77 @@ pub const fn decrypt_code(mut addr: u32, mut val: u32) -> (u32, u32) {
 /// cb1::decrypt_code_mut(&mut code.0, &mut code.1);
 /// assert_eq!(code, (0x1023CED8, 0x000003E7));
 /// ```
-pub fn decrypt_code_mut(addr: &mut u32, val: &mut u32) {
+pub const fn decrypt_code_mut(addr: &mut u32, val: &mut u32) {
     let code = decrypt_code(*addr, *val);
     *addr = code.0;
     *val = code.1;
 }
 
+/// Facts about how [`decrypt_code_explained`] decrypted a code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cb1Info {
+    /// The command of the code, which selects the seeds.
+    pub command: u8,
+    /// Whether the value was decrypted, which is only done for commands
+    /// 3 - 15. For commands 0 - 2, only the address is encrypted.
+    pub value_transformed: bool,
+}
+
+/// Like [`decrypt_code`], but also reports the command and whether the value
+/// was transformed, which helps to understand the result.
+///
+/// # Example
+/// ```
+/// use codebreaker::cb1::{self, Cb1Info};
+///
+/// let (addr, val, info) = cb1::decrypt_code_explained(0x1A11330E, 0x000003E7);
+/// assert_eq!((addr, val), (0x1023CED8, 0x000003E7));
+/// assert_eq!(info, Cb1Info { command: 0x1, value_transformed: false });
+///
+/// let (addr, val, info) = cb1::decrypt_code_explained(0x9A545CC6, 0x188CBCFB);
+/// assert_eq!((addr, val), (0x9029BEAC, 0x0C0A9225));
+/// assert_eq!(info, Cb1Info { command: 0x9, value_transformed: true });
+/// ```
+pub const fn decrypt_code_explained(addr: u32, val: u32) -> (u32, u32, Cb1Info) {
+    let cmd = command(addr);
+    let (addr, val) = decrypt_code(addr, val);
+    let info = Cb1Info {
+        command: cmd,
+        value_transformed: cmd > 2,
+    };
+    (addr, val, info)
+}
+
+/// Returns the seed for a command from one of the three seed tables, or `None`
+/// if `cmd` is not in the range 0 - 15 or `row` not in 0 - 2.
+///
+/// The command is the upper nibble of the code address. The tables are used
+/// as follows when encrypting:
+///
+/// - Row 0 is XORed with the address after rotating and adding row 1.
+/// - Row 1 is added to the lower 24 bits of the address, after rotating them
+///   so that the lowest byte becomes the highest.
+/// - Row 2 is added to the value, which is then XORed with the encrypted
+///   address. This only applies to commands 3 - 15.
+///
+/// Decryption reverses these steps.
+///
+/// # Example
+/// ```
+/// use codebreaker::cb1;
+///
+/// assert_eq!(cb1::seed(2, 0), Some(0x0af733ec));
+/// assert_eq!(cb1::seed(16, 0), None);
+/// assert_eq!(cb1::seed(2, 3), None);
+/// ```
+pub const fn seed(cmd: usize, row: usize) -> Option<u32> {
+    if cmd < 16 && row < 3 {
+        Some(SEEDS[row][cmd])
+    } else {
+        None
+    }
+}
+
 #[rustfmt::skip]
 const SEEDS: [[u32; 16]; 3] = [
     [
@@ -136,6 +218,24 @@ mod tests {
         ]
     }
 
+    #[test]
+    fn test_seed() {
+        let tests = &[
+            ((0, 0), Some(0x0a0b_8d9b)),
+            ((0, 1), Some(0x0028_8596)),
+            ((0, 2), Some(0x1dd9_a10a)),
+            ((2, 0), Some(0x0af7_33ec)),
+            ((9, 1), Some(0x0018_14d4)),
+            ((11, 2), Some(0xf9cd_94d0)),
+            ((15, 2), Some(0xc8aa_88e8)),
+            ((16, 0), None),
+            ((0, 3), None),
+        ];
+        for t in tests {
+            assert_eq!(seed(t.0 .0, t.0 .1), t.1);
+        }
+    }
+
     #[test]
     fn test_encrypt_code() {
         for t in &tests() {
@@ -169,4 +269,14 @@ mod tests {
             assert_eq!(code, t.decrypted);
         }
     }
+
+    #[test]
+    fn test_decrypt_code_explained() {
+        for t in &tests() {
+            let (addr, val, info) = decrypt_code_explained(t.encrypted.0, t.encrypted.1);
+            assert_eq!(Code(addr, val), t.decrypted);
+            assert_eq!(info.command, command(t.decrypted.0));
+            assert_eq!(info.value_transformed, info.command > 2);
+        }
+    }
 }
@@ -1,18 +1,73 @@
 //! Encrypt and decrypt cheat codes for CodeBreaker PS2 v7+.
 
-use crate::rc4::Rc4;
+use crate::rc4::{Rc4, StreamCipher};
 
 use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
 
-use bytemuck::{bytes_of, bytes_of_mut, cast_slice};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use bytemuck::{bytes_of, bytes_of_mut, cast_slice, cast_slice_mut, pod_read_unaligned};
 
 /// A processor for CB v7+ codes.
-#[derive(Clone, Copy)]
-pub struct Cb7 {
+///
+/// Words are always converted to and from bytes in little-endian order, like
+/// on the PS2, so results do not depend on the endianness of the host.
+///
+/// Processors compare equal and hash the same if they are in the same state,
+/// which allows caching states derived from beefcodes.
+///
+/// The stream cipher `C` defaults to the built-in [`Rc4`]. Use
+/// [`with_cipher`](#method.with_cipher) to plug in another [`StreamCipher`],
+/// e.g. an instrumented RC4 that logs the keystream. The other constructors,
+/// [`Default`], and [`from_bytes`](#method.from_bytes) are only implemented for
+/// the built-in cipher, so that `Cb7::new()` and friends need no type
+/// annotations.
+pub struct Cb7<C = Rc4> {
     seeds: [[u8; 256]; 5],
     key: [u32; 5],
     beefcodf: bool,
     initialized: bool,
+    rsa_enc_key: u64,
+    rsa_dec_key: u64,
+    rsa_modulus: u64,
+    cipher: PhantomData<fn() -> C>,
+}
+
+// Implemented by hand, as derive would require the same traits for C
+impl<C> Clone for Cb7<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C> Copy for Cb7<C> {}
+
+impl<C> PartialEq for Cb7<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.seeds == other.seeds
+            && self.key == other.key
+            && self.beefcodf == other.beefcodf
+            && self.initialized == other.initialized
+            && self.rsa_enc_key == other.rsa_enc_key
+            && self.rsa_dec_key == other.rsa_dec_key
+            && self.rsa_modulus == other.rsa_modulus
+    }
+}
+
+impl<C> Eq for Cb7<C> {}
+
+impl<C> Hash for Cb7<C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.seeds.hash(state);
+        self.key.hash(state);
+        self.beefcodf.hash(state);
+        self.initialized.hash(state);
+        self.rsa_enc_key.hash(state);
+        self.rsa_dec_key.hash(state);
+        self.rsa_modulus.hash(state);
+    }
 }
 
 /// Implements the default CB v7 encryption used by former CMGSCCC.com.
@@ -26,10 +81,10 @@ impl Default for Cb7 {
     }
 }
 
-impl fmt::Debug for Cb7 {
+impl<C> fmt::Debug for Cb7<C> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Cb7")
-            .field("seeds[0][0..16]", &self.seeds[0][0..16].to_vec())
+            .field("seeds[0][0..16]", &&self.seeds[0][0..16])
             .field("key", &self.key)
             .field("beefcodf", &self.beefcodf)
             .field("initialized", &self.initialized)
@@ -41,14 +96,137 @@ impl Cb7 {
     /// Returns a new processor for encrypting and decrypting a list of CB v7+
     /// codes.
     pub const fn new() -> Self {
+        Self::with_rsa_params(RSA_ENC_KEY, RSA_DEC_KEY, RSA_MODULUS)
+    }
+
+    /// Returns the same state as [`default`](#impl-Default-for-Cb7), which is
+    /// `BEEFC0DE 00000000` applied to [`new`](#method.new), but computed at
+    /// compile time when used in a constant.
+    pub(crate) const fn default_const() -> Self {
+        let mut cb7 = Self::new();
+        cb7.key = RC4_KEY;
+        cb7.initialized = true;
+
+        // Same as beefcode() for an uninitialized processor and seed value 0,
+        // where the seeds start out as zeros
+        let mut key = [0; 20];
+        let mut i = 0;
+        while i < 5 {
+            let bytes = cb7.key[i].to_le_bytes();
+            let mut j = 0;
+            while j < 4 {
+                key[4 * i + j] = bytes[j];
+                j += 1;
+            }
+            i += 1;
+        }
+        i = 0;
+        while i < 5 {
+            let mut rc4 = Rc4::new(&key);
+            rc4.crypt(&mut cb7.seeds[i]);
+            rc4.crypt(&mut key);
+            i += 1;
+        }
+        i = 0;
+        while i < 5 {
+            cb7.key[i] = u32::from_le_bytes([key[4 * i], key[4 * i + 1], key[4 * i + 2], key[4 * i + 3]]);
+            i += 1;
+        }
+        cb7
+    }
+
+    /// Returns a new processor with the given beefcode already applied.
+    ///
+    /// This is the same as calling [`new`](#method.new) followed by
+    /// [`beefcode`](#method.beefcode).
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::cb7::Cb7;
+    ///
+    /// let mut cb7 = Cb7::with_beefcode(0xBEEFC0DE, 0xDEADFACE);
+    /// assert_eq!(cb7.decrypt_code(0xE65B5422, 0xB12543CF), (0x9029BEAC, 0x0C0A9225));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the passed code is not a "beefcode".
+    pub fn with_beefcode(addr: u32, val: u32) -> Self {
+        assert!(is_beefcode(addr), "not a beefcode: {addr:08X} {val:08X}");
+        let mut cb7 = Self::new();
+        cb7.beefcode(addr, val);
+        cb7
+    }
+
+    /// Returns a new processor like [`new`](#method.new), but with custom RSA
+    /// parameters instead of the ones used by CodeBreaker.
+    ///
+    /// This is only useful for experimenting with variants of the format.
+    /// Decryption undoes encryption if `dec_key` is the inverse of `enc_key`
+    /// modulo phi(`modulus`), and the modulus must be at least 2^32.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::cb7::Cb7;
+    ///
+    /// let mut cb7 = Cb7::with_rsa_params(3, 12297829382473034371, 18446744073709551557);
+    /// cb7.beefcode(0xBEEFC0DE, 0x00000000);
+    /// let code = cb7.encrypt_code(0x2043AFCC, 0x2411FFFF);
+    /// assert_eq!(cb7.decrypt_code(code.0, code.1), (0x2043AFCC, 0x2411FFFF));
+    /// ```
+    pub const fn with_rsa_params(enc_key: u64, dec_key: u64, modulus: u64) -> Self {
+        Self::with_params(enc_key, dec_key, modulus)
+    }
+}
+
+impl<C: StreamCipher> Cb7<C> {
+    /// Returns a new processor like [`new`](#method.new), but using the
+    /// stream cipher `C` instead of the built-in [`Rc4`].
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::cb7::Cb7;
+    /// use codebreaker::rc4::Rc4;
+    ///
+    /// let mut cb7 = Cb7::<Rc4>::with_cipher();
+    /// cb7.beefcode(0xBEEFC0DE, 0x00000000);
+    /// assert_eq!(cb7, Cb7::default());
+    /// ```
+    pub const fn with_cipher() -> Self {
+        Self::with_params(RSA_ENC_KEY, RSA_DEC_KEY, RSA_MODULUS)
+    }
+
+    const fn with_params(enc_key: u64, dec_key: u64, modulus: u64) -> Self {
         Self {
             seeds: ZERO_SEEDS,
             key: [0; 5],
             beefcodf: false,
             initialized: false,
+            rsa_enc_key: enc_key,
+            rsa_dec_key: dec_key,
+            rsa_modulus: modulus,
+            cipher: PhantomData,
         }
     }
 
+    /// Resets the processor to the state returned by [`new`](#method.new),
+    /// keeping any custom RSA parameters.
+    ///
+    /// Call this before reusing the processor for another, independent list.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::cb7::Cb7;
+    ///
+    /// let mut cb7 = Cb7::default();
+    /// cb7.reset();
+    /// cb7.beefcode(0xBEEFC0DE, 0xDEADFACE);
+    /// assert_eq!(cb7.decrypt_code(0xE65B5422, 0xB12543CF), (0x9029BEAC, 0x0C0A9225));
+    /// ```
+    pub const fn reset(&mut self) {
+        *self = Self::with_params(self.rsa_enc_key, self.rsa_dec_key, self.rsa_modulus);
+    }
+
     /// Generates or changes the encryption key and seeds.
     ///
     /// Needs to be called for every "beefcode", which comes in two flavors:
@@ -78,6 +256,11 @@ impl Cb7 {
     /// Panics if the passed code is not a "beefcode".
     pub fn beefcode(&mut self, addr: u32, val: u32) {
         assert!(is_beefcode(addr));
+        trace_event!(
+            addr = format_args!("{addr:08X}"),
+            val = format_args!("{val:08X}"),
+            "beefcode"
+        );
 
         // Easily access all bytes of val as indices into seeds
         let mut idx = [0; 4];
@@ -120,14 +303,16 @@ impl Cb7 {
         }
 
         // Use key to encrypt seeds with RC4
-        let k = bytes_of_mut(&mut self.key);
+        let mut key = self.key.map(u32::to_le);
+        let k = bytes_of_mut(&mut key);
         for i in 0..5 {
-            let mut rc4 = Rc4::new(k);
+            let mut rc4 = C::new(k);
             // Encrypt seeds
             rc4.crypt(&mut self.seeds[i]);
             // Encrypt original key for next round
             rc4.crypt(k);
         }
+        self.key = key.map(u32::from_le);
 
         // Since we don't know the extra seed value of BEEFC0DF yet,
         // all we can do is set a flag.
@@ -136,6 +321,11 @@ impl Cb7 {
 
     /// Encrypts a code and returns the result.
     ///
+    /// Decrypting the result with the same processor state always returns the
+    /// original code. Internally, the RSA step can only transform 64-bit values
+    /// below the modulus `FFFFFFFF FFFFFFF5`; the few values at or above it are
+    /// passed through unchanged, which decryption then passes through as well.
+    ///
     /// # Example
     /// ```
     /// use codebreaker::cb7::Cb7;
@@ -152,6 +342,15 @@ impl Cb7 {
 
     /// Encrypts a code directly.
     ///
+    /// A beefcode is encrypted with the current state, which is then reseeded
+    /// from the raw beefcode, the same way
+    /// [`decrypt_code_mut`](#method.decrypt_code_mut) reseeds from the
+    /// decrypted one. CodeBreaker itself, however, reseeds
+    /// from a beefcode once more after processing it. To get the same codes as
+    /// the device, call [`beefcode`](#method.beefcode) again after encrypting a
+    /// beefcode, or use [`encrypt_block_mut`](#method.encrypt_block_mut) or
+    /// [`Codebreaker`](crate::Codebreaker), which take care of this.
+    ///
     /// # Example
     /// ```
     /// use codebreaker::cb7::Cb7;
@@ -170,20 +369,21 @@ impl Cb7 {
         *val = mul_encrypt(*val, self.key[2].wrapping_add(self.key[3]));
 
         // Step 2: RC4
-        let mut code = [*addr, *val];
-        let mut rc4 = Rc4::new(bytes_of(&self.key));
+        let mut code = [*addr, *val].map(u32::to_le);
+        let mut rc4 = C::new(bytes_of(&self.key.map(u32::to_le)));
         rc4.crypt(bytes_of_mut(&mut code));
-        *addr = code[0];
-        *val = code[1];
+        *addr = u32::from_le(code[0]);
+        *val = u32::from_le(code[1]);
 
         // Step 3: RSA
-        rsa_crypt(addr, val, RSA_ENC_KEY, RSA_MODULUS);
+        rsa_crypt(addr, val, self.rsa_enc_key, self.rsa_modulus);
 
         // Step 4: Encryption loop of 64 cycles, using the generated seeds
-        let s: &[u32] = cast_slice(&self.seeds);
+        let words: &[u32] = cast_slice(&self.seeds);
+        let s = |i: usize| u32::from_le(words[i]);
         for i in 0..64 {
-            *addr = (addr.wrapping_add(s[2 * 64 + i]) ^ s[i]).wrapping_sub(*val ^ s[4 * 64 + i]);
-            *val = (val.wrapping_sub(s[3 * 64 + i]) ^ s[64 + i]).wrapping_add(*addr ^ s[4 * 64 + i]);
+            *addr = (addr.wrapping_add(s(2 * 64 + i)) ^ s(i)).wrapping_sub(*val ^ s(4 * 64 + i));
+            *val = (val.wrapping_sub(s(3 * 64 + i)) ^ s(64 + i)).wrapping_add(*addr ^ s(4 * 64 + i));
         }
 
         // BEEFC0DE
@@ -195,12 +395,67 @@ impl Cb7 {
         // BEEFC0DF uses two codes. If the previous code was the first of the
         // two, use the current one to encrypt the seeds.
         if self.beefcodf {
-            let mut rc4 = Rc4::new(bytes_of(&[oldaddr, oldval]));
-            rc4.crypt(bytes_of_mut(&mut self.seeds));
-            self.beefcodf = false;
+            self.crypt_seeds(oldaddr, oldval);
         }
     }
 
+    /// Returns true if decrypting the encrypted code with the current state
+    /// gives back the original code. The state is not changed.
+    ///
+    /// With the RSA parameters of CodeBreaker, this holds for every code: the
+    /// RSA step leaves the few intermediate values at or above the modulus
+    /// unchanged in both directions, so they are merely not transformed by
+    /// that step. Custom parameters passed to
+    /// [`with_rsa_params`](#method.with_rsa_params) that are not inverse to
+    /// each other, however, break round-trips for most codes.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::cb7::Cb7;
+    ///
+    /// let cb7 = Cb7::default();
+    /// assert!(cb7.can_roundtrip(0x2043AFCC, 0x2411FFFF));
+    /// assert!(cb7.can_roundtrip(0xFFFFFFFF, 0xFFFFFFFF));
+    ///
+    /// let mut cb7 = Cb7::with_rsa_params(3, 3, 18446744073709551557);
+    /// cb7.beefcode(0xBEEFC0DE, 0x00000000);
+    /// assert!(!cb7.can_roundtrip(0x2043AFCC, 0x2411FFFF));
+    /// ```
+    pub fn can_roundtrip(&self, addr: u32, val: u32) -> bool {
+        let (mut enc, mut dec) = (*self, *self);
+        let code = enc.encrypt_code(addr, val);
+        dec.decrypt_code(code.0, code.1) == (addr, val)
+    }
+
+    /// Like [`encrypt_code`](#method.encrypt_code), but fails instead of
+    /// producing meaningless output if no beefcode has been applied yet.
+    ///
+    /// Use [`default`](#impl-Default-for-Cb7),
+    /// [`with_beefcode`](#method.with_beefcode), or
+    /// [`beefcode`](#method.beefcode) to initialize the processor first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Uninitialized`] without changing any state if the processor
+    /// has not been initialized with a beefcode.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::cb7::{Cb7, Uninitialized};
+    ///
+    /// let mut cb7 = Cb7::new();
+    /// assert_eq!(cb7.encrypt_code_checked(0x2043AFCC, 0x2411FFFF), Err(Uninitialized));
+    ///
+    /// cb7.beefcode(0xBEEFC0DE, 0x00000000);
+    /// assert_eq!(cb7.encrypt_code_checked(0x2043AFCC, 0x2411FFFF), Ok((0x397951B0, 0x41569FE0)));
+    /// ```
+    pub fn encrypt_code_checked(&mut self, addr: u32, val: u32) -> Result<(u32, u32), Uninitialized> {
+        if !self.initialized {
+            return Err(Uninitialized);
+        }
+        Ok(self.encrypt_code(addr, val))
+    }
+
     /// Decrypts a code and returns the result.
     ///
     /// # Example
@@ -230,21 +485,22 @@ impl Cb7 {
     /// ```
     pub fn decrypt_code_mut(&mut self, addr: &mut u32, val: &mut u32) {
         // Step 1: Decryption loop of 64 cycles, using the generated seeds
-        let s: &[u32] = cast_slice(&self.seeds);
+        let words: &[u32] = cast_slice(&self.seeds);
+        let s = |i: usize| u32::from_le(words[i]);
         for i in (0..64).rev() {
-            *val = (val.wrapping_sub(*addr ^ s[4 * 64 + i]) ^ s[64 + i]).wrapping_add(s[3 * 64 + i]);
-            *addr = (addr.wrapping_add(*val ^ s[4 * 64 + i]) ^ s[i]).wrapping_sub(s[2 * 64 + i]);
+            *val = (val.wrapping_sub(*addr ^ s(4 * 64 + i)) ^ s(64 + i)).wrapping_add(s(3 * 64 + i));
+            *addr = (addr.wrapping_add(*val ^ s(4 * 64 + i)) ^ s(i)).wrapping_sub(s(2 * 64 + i));
         }
 
         // Step 2: RSA
-        rsa_crypt(addr, val, RSA_DEC_KEY, RSA_MODULUS);
+        rsa_crypt(addr, val, self.rsa_dec_key, self.rsa_modulus);
 
         // Step 3: RC4
-        let mut code = [*addr, *val];
-        let mut rc4 = Rc4::new(bytes_of(&self.key));
+        let mut code = [*addr, *val].map(u32::to_le);
+        let mut rc4 = C::new(bytes_of(&self.key.map(u32::to_le)));
         rc4.crypt(bytes_of_mut(&mut code));
-        *addr = code[0];
-        *val = code[1];
+        *addr = u32::from_le(code[0]);
+        *val = u32::from_le(code[1]);
 
         // Step 4: Multiplication with multiplicative inverse, modulo (2^32)
         *addr = mul_decrypt(*addr, self.key[0].wrapping_sub(self.key[1]));
@@ -253,9 +509,7 @@ impl Cb7 {
         // BEEFC0DF uses two codes. If the previous code was the first of the
         // two, use the current one to decrypt the seeds.
         if self.beefcodf {
-            let mut rc4 = Rc4::new(bytes_of(&[*addr, *val]));
-            rc4.crypt(bytes_of_mut(&mut self.seeds));
-            self.beefcodf = false;
+            self.crypt_seeds(*addr, *val);
             return;
         }
 
@@ -264,8 +518,349 @@ impl Cb7 {
             self.beefcode(*addr, *val);
         }
     }
+
+    /// Supplies the extra seed line following a `BEEFC0DF` code directly.
+    ///
+    /// Normally, the extra seed line is passed to
+    /// [`encrypt_code`](#method.encrypt_code) or
+    /// [`decrypt_code`](#method.decrypt_code) like any other code. This method
+    /// only applies the (decrypted) line to the seeds, making explicit that it
+    /// is not a regular code.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NotExpectingBeefcodf`] if the previous code was not a
+    /// `BEEFC0DF` code.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::cb7::{Cb7, NotExpectingBeefcodf};
+    ///
+    /// let mut cb7 = Cb7::new();
+    /// cb7.beefcode(0xBEEFC0DF, 0xB16B00B5);
+    /// assert_eq!(cb7.supply_beefcodf_line(0x01234567, 0x89ABCDEF), Ok(()));
+    /// assert_eq!(cb7.encrypt_code(0x9029BEAC, 0x0C0A9225), (0x06133B66, 0x95444FF1));
+    /// assert_eq!(cb7.supply_beefcodf_line(0x01234567, 0x89ABCDEF), Err(NotExpectingBeefcodf));
+    /// ```
+    pub fn supply_beefcodf_line(&mut self, addr: u32, val: u32) -> Result<(), NotExpectingBeefcodf> {
+        if !self.beefcodf {
+            return Err(NotExpectingBeefcodf);
+        }
+        self.crypt_seeds(addr, val);
+        Ok(())
+    }
+
+    // Encrypts the seeds with the extra seed line of BEEFC0DF
+    fn crypt_seeds(&mut self, addr: u32, val: u32) {
+        let mut rc4 = C::new(bytes_of(&[addr.to_le(), val.to_le()]));
+        rc4.crypt(bytes_of_mut(&mut self.seeds));
+        self.beefcodf = false;
+    }
+
+    /// Decrypts codes up to and including the next beefcode and returns the
+    /// decrypted codes together with the number of codes consumed.
+    ///
+    /// A `BEEFC0DF` beefcode also consumes the following line holding the extra
+    /// seed value. Call this repeatedly on the remaining codes to process a
+    /// whole list block by block.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::cb7::Cb7;
+    ///
+    /// let encrypted = [
+    ///     (0xFE8B8601, 0xC7C6F6CE),
+    ///     (0x2195D855, 0x63FA11A7),
+    ///     (0x0CA31760, 0xA6F7E88A),
+    /// ];
+    ///
+    /// let mut cb7 = Cb7::default();
+    /// let (decrypted, n) = cb7.decrypt_block(&encrypted);
+    /// assert_eq!(decrypted, [(0xBEEFC0DF, 0xB16B00B5), (0x01234567, 0x89ABCDEF)]);
+    /// assert_eq!(n, 2);
+    ///
+    /// let (decrypted, n) = cb7.decrypt_block(&encrypted[n..]);
+    /// assert_eq!(decrypted, [(0x9029BEAC, 0x0C0A9225)]);
+    /// assert_eq!(n, 1);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn decrypt_block(&mut self, codes: &[(u32, u32)]) -> (Vec<(u32, u32)>, usize) {
+        let mut output = Vec::new();
+        let mut extra_seed = false;
+
+        for &(mut addr, mut val) in codes {
+            self.decrypt_code_mut(&mut addr, &mut val);
+            output.push((addr, val));
+
+            if extra_seed {
+                break;
+            }
+            if is_beefcode(addr) {
+                self.beefcode(addr, val);
+                if addr & 1 == 0 {
+                    break;
+                }
+                extra_seed = true;
+            }
+        }
+
+        let consumed = output.len();
+        (output, consumed)
+    }
+
+    /// Encrypts a block of codes in place.
+    ///
+    /// The first code must be the raw beefcode that sets up the encryption and
+    /// is left untouched, as it is encrypted with CB v1 as part of the header.
+    /// Beefcodes within the block are handled automatically.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::cb7::Cb7;
+    ///
+    /// let mut codes = [(0xBEEFC0DE, 0x00000000), (0x9029BEAC, 0x0C0A9225)];
+    /// let mut cb7 = Cb7::new();
+    /// cb7.encrypt_block_mut(&mut codes);
+    /// assert_eq!(codes, [(0xBEEFC0DE, 0x00000000), (0xD08F3A49, 0x00078A53)]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the first code is not a beefcode.
+    pub fn encrypt_block_mut(&mut self, codes: &mut [(u32, u32)]) {
+        let Some(((addr, val), rest)) = codes.split_first_mut() else {
+            return;
+        };
+        self.beefcode(*addr, *val);
+
+        for (addr, val) in rest {
+            let raw = (*addr, *val);
+            self.encrypt_code_mut(addr, val);
+            if is_beefcode(raw.0) {
+                self.beefcode(raw.0, raw.1);
+            }
+        }
+    }
+
+    /// Decrypts a block of codes in place.
+    ///
+    /// This is the counterpart of
+    /// [`encrypt_block_mut`](#method.encrypt_block_mut). The first code must
+    /// be the raw beefcode, which is left untouched.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::cb7::Cb7;
+    ///
+    /// let mut codes = [(0xBEEFC0DE, 0x00000000), (0xD08F3A49, 0x00078A53)];
+    /// let mut cb7 = Cb7::new();
+    /// cb7.decrypt_block_mut(&mut codes);
+    /// assert_eq!(codes, [(0xBEEFC0DE, 0x00000000), (0x9029BEAC, 0x0C0A9225)]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the first code is not a beefcode.
+    pub fn decrypt_block_mut(&mut self, codes: &mut [(u32, u32)]) {
+        let Some(((addr, val), rest)) = codes.split_first_mut() else {
+            return;
+        };
+        self.beefcode(*addr, *val);
+
+        for (addr, val) in rest {
+            self.decrypt_code_mut(addr, val);
+            if is_beefcode(*addr) {
+                self.beefcode(*addr, *val);
+            }
+        }
+    }
+
+    /// Checks that the processor is in a clean state at the end of a list.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DanglingBeefcodf`] if the list ended with a `BEEFC0DF` code
+    /// whose extra seed line is missing. The pending state is cleared in that
+    /// case.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::cb7::{Cb7, DanglingBeefcodf};
+    ///
+    /// let mut cb7 = Cb7::default();
+    /// cb7.decrypt_code(0xFE8B8601, 0xC7C6F6CE); // BEEFC0DF B16B00B5
+    /// assert_eq!(cb7.finish(), Err(DanglingBeefcodf));
+    /// assert_eq!(cb7.finish(), Ok(()));
+    /// ```
+    pub const fn finish(&mut self) -> Result<(), DanglingBeefcodf> {
+        if self.beefcodf {
+            self.beefcodf = false;
+            return Err(DanglingBeefcodf);
+        }
+        Ok(())
+    }
+
+    /// Prepares the processor for the next of several concatenated lists.
+    ///
+    /// This discards a pending `BEEFC0DF` whose extra seed line is missing at
+    /// the end of the previous list, so that the first line of the next list
+    /// is not mistaken for it. The key and seeds derived so far are kept, as
+    /// they would be when a device processes the concatenated lists as one.
+    /// A list that was encrypted independently starts with its own beefcode,
+    /// which re-seeds the processor anyway; use [`reset`](#method.reset) to
+    /// also start from scratch before that beefcode.
+    pub const fn begin_list(&mut self) {
+        self.beefcodf = false;
+    }
+
+    /// Compares the seeds of two processors and returns the position
+    /// `(block, index)` of the first seed byte that differs, or `None` if all
+    /// seeds are the same.
+    ///
+    /// This is useful for debugging ports of the algorithm.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::cb7::Cb7;
+    ///
+    /// let mut cb7 = Cb7::new();
+    /// cb7.beefcode(0xBEEFC0DE, 0x00000000);
+    /// assert_eq!(cb7.seeds_differ(&Cb7::default()), None);
+    ///
+    /// cb7.beefcode(0xBEEFC0DE, 0xDEADFACE);
+    /// assert!(cb7.seeds_differ(&Cb7::default()).is_some());
+    /// ```
+    pub fn seeds_differ(&self, other: &Self) -> Option<(usize, usize)> {
+        self.seeds
+            .iter()
+            .zip(&other.seeds)
+            .enumerate()
+            .find_map(|(block, (a, b))| a.iter().zip(b).position(|(x, y)| x != y).map(|index| (block, index)))
+    }
+}
+
+impl Cb7 {
+    /// The length of the state returned by [`to_bytes`](#method.to_bytes).
+    pub const STATE_LEN: usize = 1325;
+
+    /// Serializes the state of the processor, so that it can be restored with
+    /// [`from_bytes`](#method.from_bytes), e.g. by a later run of the program.
+    ///
+    /// The layout is fixed and independent of the host: the seeds (5 x 256
+    /// bytes), followed by the key (5 words) and the RSA parameters (encryption
+    /// key, decryption key, modulus), all in little-endian order, and a final
+    /// byte of flags.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::cb7::Cb7;
+    ///
+    /// let cb7 = Cb7::with_beefcode(0xBEEFC0DE, 0xDEADFACE);
+    /// let bytes = cb7.to_bytes();
+    /// assert_eq!(Cb7::from_bytes(&bytes), Ok(cb7));
+    /// ```
+    pub fn to_bytes(&self) -> [u8; Self::STATE_LEN] {
+        let mut bytes = [0; Self::STATE_LEN];
+        let (seeds, rest) = bytes.split_at_mut(SEEDS_LEN);
+        seeds.copy_from_slice(cast_slice(&self.seeds));
+        let (key, rest) = rest.split_at_mut(20);
+        key.copy_from_slice(bytes_of(&self.key.map(u32::to_le)));
+        let (rsa, flags) = rest.split_at_mut(24);
+        let rsa_params = [self.rsa_enc_key, self.rsa_dec_key, self.rsa_modulus];
+        rsa.copy_from_slice(bytes_of(&rsa_params.map(u64::to_le)));
+        flags[0] = u8::from(self.beefcodf) | u8::from(self.initialized) << 1;
+        bytes
+    }
+
+    /// Restores a processor from the state returned by
+    /// [`to_bytes`](#method.to_bytes).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StateError`] if `bytes` is not [`STATE_LEN`](#associatedconstant.STATE_LEN)
+    /// bytes long or has unknown flags set.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, StateError> {
+        if bytes.len() != Self::STATE_LEN {
+            return Err(StateError::Length(bytes.len()));
+        }
+        let flags = bytes[Self::STATE_LEN - 1];
+        if flags & !0b11 != 0 {
+            return Err(StateError::Flags(flags));
+        }
+
+        let mut cb7 = Self::new();
+        cast_slice_mut(&mut cb7.seeds).copy_from_slice(&bytes[..SEEDS_LEN]);
+        cb7.key = pod_read_unaligned::<[u32; 5]>(&bytes[SEEDS_LEN..SEEDS_LEN + 20]).map(u32::from_le);
+        let [enc_key, dec_key, modulus] =
+            pod_read_unaligned::<[u64; 3]>(&bytes[SEEDS_LEN + 20..SEEDS_LEN + 44]).map(u64::from_le);
+        cb7.rsa_enc_key = enc_key;
+        cb7.rsa_dec_key = dec_key;
+        cb7.rsa_modulus = modulus;
+        cb7.beefcodf = flags & 0b01 != 0;
+        cb7.initialized = flags & 0b10 != 0;
+        Ok(cb7)
+    }
+}
+
+/// Error returned by [`Cb7::finish`] if a `BEEFC0DF` code is missing its extra
+/// seed line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DanglingBeefcodf;
+
+impl fmt::Display for DanglingBeefcodf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BEEFC0DF code is missing its extra seed line")
+    }
+}
+
+impl core::error::Error for DanglingBeefcodf {}
+
+/// Error returned by [`Cb7::supply_beefcodf_line`] if the previous code was
+/// not a `BEEFC0DF` code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotExpectingBeefcodf;
+
+impl fmt::Display for NotExpectingBeefcodf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("not expecting the extra seed line of a BEEFC0DF code")
+    }
+}
+
+impl core::error::Error for NotExpectingBeefcodf {}
+
+/// Error returned by [`Cb7::encrypt_code_checked`] if no beefcode has been
+/// applied yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Uninitialized;
+
+impl fmt::Display for Uninitialized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("processor is not initialized with a beefcode")
+    }
+}
+
+impl core::error::Error for Uninitialized {}
+
+/// Error returned by [`Cb7::from_bytes`] if the state is invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateError {
+    /// The state does not have the expected length; holds the actual length.
+    Length(usize),
+    /// The flags byte has unknown bits set; holds the flags byte.
+    Flags(u8),
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Length(len) => write!(f, "expected state of {} bytes, got {len}", Cb7::STATE_LEN),
+            Self::Flags(flags) => write!(f, "unknown state flags {flags:#04x}"),
+        }
+    }
 }
 
+impl core::error::Error for StateError {}
+
 /// Returns true if the code address indicates a "beefcode". In that case, the
 /// [`beefcode`](struct.Cb7.html#method.beefcode) method should be invoked.
 ///
@@ -282,6 +877,21 @@ pub const fn is_beefcode(addr: u32) -> bool {
     addr & 0xffff_fffe == BEEFCODE
 }
 
+/// Returns the header line that starts a list of CB v7 codes encrypted with
+/// the given seed value.
+///
+/// The header is the beefcode `BEEFC0DE vvvvvvvv` encrypted with CB v1.
+///
+/// # Example
+/// ```
+/// use codebreaker::cb7::header_for_seed;
+///
+/// assert_eq!(header_for_seed(0), (0xB4336FA9, 0x4DFEFB79));
+/// ```
+pub const fn header_for_seed(val: u32) -> (u32, u32) {
+    crate::cb1::encrypt_code(BEEFCODE, val)
+}
+
 // Multiplication, modulo (2^32)
 #[inline]
 const fn mul_encrypt(a: u32, b: u32) -> u32 {
@@ -309,21 +919,57 @@ const fn mod_inverse(x: u32) -> u32 {
 }
 
 // RSA encryption/decryption
+//
+// Codes >= modulus are left unchanged, so that encryption and decryption are
+// still inverse to each other.
 fn rsa_crypt(addr: &mut u32, val: &mut u32, rsakey: u64, modulus: u64) {
+    let code = (u64::from(*addr) << 32) | u64::from(*val);
+
+    // Exponentiation is only invertible if code < modulus
+    if code < modulus {
+        let result = modpow(code, rsakey, modulus);
+        *addr = (result >> 32) as u32;
+        *val = result as u32;
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+fn modpow(base: u64, exp: u64, modulus: u64) -> u64 {
+    modpow_bigint(base, exp, modulus)
+}
+
+#[cfg(not(feature = "num-bigint"))]
+const fn modpow(base: u64, exp: u64, modulus: u64) -> u64 {
+    modpow_u128(base, exp, modulus)
+}
+
+#[cfg(feature = "num-bigint")]
+fn modpow_bigint(base: u64, exp: u64, modulus: u64) -> u64 {
     use num_bigint::BigUint;
 
-    let code = BigUint::from_slice(&[*val, *addr]);
-    let m = BigUint::from(modulus);
+    let result = BigUint::from(base).modpow(&BigUint::from(exp), &BigUint::from(modulus));
+    // Missing high digits are zero
+    let digits = result.to_u64_digits();
+    digits.first().copied().unwrap_or(0)
+}
 
-    // Exponentiation is only invertible if code < modulus
-    if code < m {
-        let digits = code.modpow(&BigUint::from(rsakey), &m).to_u32_digits();
-        *addr = digits[1];
-        *val = digits[0];
+// Square-and-multiply; products of two values < 2^64 fit into a u128
+#[cfg(any(not(feature = "num-bigint"), test))]
+const fn modpow_u128(base: u64, mut exp: u64, modulus: u64) -> u64 {
+    let m = modulus as u128;
+    let mut base = base as u128 % m;
+    let mut result = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % m;
+        }
+        base = base * base % m;
+        exp >>= 1;
     }
+    result as u64
 }
 
-const BEEFCODE: u32 = 0xbeef_c0de;
+pub(crate) const BEEFCODE: u32 = 0xbeef_c0de;
 
 const RC4_KEY: [u32; 5] = [0xd0db_a9d7, 0x13a0_a96c, 0x8041_0df0, 0x2ccd_be1f, 0xe570_a86b];
 
@@ -351,6 +997,7 @@ const RSA_ENC_KEY: u64 = 2_682_110_966_135_737_091;
 const RSA_MODULUS: u64 = 18_446_744_073_709_551_605; // 0xffff_ffff_ffff_fff5
 
 const ZERO_SEEDS: [[u8; 256]; 5] = [[0; 256]; 5];
+const SEEDS_LEN: usize = 5 * 256;
 
 #[rustfmt::skip]
 const SEEDS: [[u8; 256]; 5] = [
@@ -481,6 +1128,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_beefcode_key() {
+        let tests = &[
+            (
+                (BEEFCODE, 0x0000_0000),
+                [0x16fa_bd17, 0xc721_8b18, 0xdeb6_fdd7, 0xc7cf_d5df, 0x0b03_5d57],
+            ),
+            (
+                (BEEFCODE, 0xdead_face),
+                [0x095e_54c7, 0xfc2d_2284, 0xb397_2452, 0x5b66_5ddf, 0xaf97_bfc2],
+            ),
+        ];
+        for t in tests {
+            let mut cb7 = Cb7::new();
+            cb7.beefcode(t.0 .0, t.0 .1);
+            assert_eq!(cb7.key, t.1);
+        }
+    }
+
+    #[test]
+    fn test_header_for_seed() {
+        assert_eq!(header_for_seed(0), (0xb433_6fa9, 0x4dfe_fb79));
+
+        for t in tests().iter().filter(|t| t.beefcode.0 == BEEFCODE) {
+            let header = header_for_seed(t.beefcode.1);
+            let mut cb = crate::Codebreaker::new();
+            let result: Code = cb.decrypt_code(header.0, header.1).into();
+            assert_eq!(result, t.beefcode);
+            for (i, &code) in t.encrypted.iter().enumerate() {
+                let result: Code = cb.decrypt_code(code.0, code.1).into();
+                assert_eq!(result, t.decrypted[i]);
+            }
+        }
+    }
+
     #[test]
     fn test_mod_inverse() {
         let tests = &[
@@ -607,6 +1289,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_encrypt_beefcode_standalone() {
+        for t in tests().iter().filter(|t| t.decrypted[0].is_beefcode()) {
+            let (raw, rest) = t.decrypted.split_first().unwrap();
+
+            // The beefcode line itself is encrypted like any other code
+            let mut cb7 = Cb7::new();
+            cb7.beefcode(t.beefcode.0, t.beefcode.1);
+            let code: Code = cb7.encrypt_code(raw.0, raw.1).into();
+            assert_eq!(code, t.encrypted[0]);
+
+            // Reseeding once is enough to round-trip through Cb7 alone...
+            let mut encrypted = vec![code];
+            encrypted.extend(rest.iter().map(|c| Code::from(cb7.encrypt_code(c.0, c.1))));
+            let mut cb7 = Cb7::new();
+            cb7.beefcode(t.beefcode.0, t.beefcode.1);
+            let decrypted: Vec<Code> = encrypted.iter().map(|c| cb7.decrypt_code(c.0, c.1).into()).collect();
+            assert_eq!(decrypted, t.decrypted);
+
+            // ...but the device reseeds twice, as does Codebreaker
+            let mut cb7 = Cb7::new();
+            cb7.beefcode(t.beefcode.0, t.beefcode.1);
+            cb7.encrypt_code(raw.0, raw.1);
+            cb7.beefcode(raw.0, raw.1);
+            let encrypted: Vec<Code> = rest.iter().map(|c| cb7.encrypt_code(c.0, c.1).into()).collect();
+            assert_eq!(encrypted, t.encrypted[1..]);
+        }
+    }
+
     #[test]
     fn test_encrypt_code_mut() {
         for t in &mut tests() {
@@ -642,6 +1353,441 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_encrypt_block_mut() {
+        for t in &tests() {
+            let mut codes: Vec<(u32, u32)> = core::iter::once(&t.beefcode)
+                .chain(&t.decrypted)
+                .map(Code::as_tuple)
+                .collect();
+            let mut cb7 = Cb7::new();
+            cb7.encrypt_block_mut(&mut codes);
+            assert_eq!(codes[0], t.beefcode.as_tuple());
+            assert!(codes[1..]
+                .iter()
+                .map(|&c| Code::from(c))
+                .eq(t.encrypted.iter().copied()));
+        }
+    }
+
+    #[test]
+    fn test_decrypt_block_mut() {
+        for t in &tests() {
+            let mut codes: Vec<(u32, u32)> = core::iter::once(&t.beefcode)
+                .chain(&t.encrypted)
+                .map(Code::as_tuple)
+                .collect();
+            let mut cb7 = Cb7::new();
+            cb7.decrypt_block_mut(&mut codes);
+            assert_eq!(codes[0], t.beefcode.as_tuple());
+            assert!(codes[1..]
+                .iter()
+                .map(|&c| Code::from(c))
+                .eq(t.decrypted.iter().copied()));
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_decrypt_block() {
+        // BEEFC0DE & BEEFC0DF
+        let t = &tests()[3];
+        let encrypted: Vec<(u32, u32)> = t.encrypted.iter().map(Code::as_tuple).collect();
+
+        let mut cb7 = Cb7::new();
+        cb7.beefcode(t.beefcode.0, t.beefcode.1);
+
+        let (decrypted, n) = cb7.decrypt_block(&encrypted);
+        assert_eq!(n, 2);
+        assert_eq!(
+            decrypted.iter().map(|&c| c.into()).collect::<Vec<Code>>(),
+            t.decrypted[..2]
+        );
+
+        let (decrypted, n) = cb7.decrypt_block(&encrypted[2..]);
+        assert_eq!(n, 3);
+        assert_eq!(
+            decrypted.iter().map(|&c| c.into()).collect::<Vec<Code>>(),
+            t.decrypted[2..]
+        );
+
+        let (decrypted, n) = cb7.decrypt_block(&[]);
+        assert_eq!(n, 0);
+        assert!(decrypted.is_empty());
+    }
+
+    #[test]
+    fn test_with_beefcode() {
+        for t in &tests() {
+            let mut cb7 = Cb7::with_beefcode(t.beefcode.0, t.beefcode.1);
+            for (i, &code) in t.encrypted.iter().enumerate() {
+                let result: Code = cb7.decrypt_code(code.0, code.1).into();
+                assert_eq!(result, t.decrypted[i]);
+
+                if is_beefcode(result.0) {
+                    cb7.beefcode(result.0, result.1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "not a beefcode: 2043AFCC 2411FFFF")]
+    fn test_with_beefcode_invalid() {
+        let _ = Cb7::with_beefcode(0x2043_afcc, 0x2411_ffff);
+    }
+
+    // XORs each byte with the XOR of all key bytes
+    struct MockCipher(u8);
+
+    impl StreamCipher for MockCipher {
+        fn new(key: &[u8]) -> Self {
+            Self(key.iter().fold(0, |acc, b| acc ^ b))
+        }
+
+        fn crypt(&mut self, buf: &mut [u8]) {
+            buf.iter_mut().for_each(|b| *b ^= self.0);
+        }
+    }
+
+    #[test]
+    fn test_with_cipher() {
+        for t in &tests() {
+            // The built-in cipher gives the same results as new()
+            let mut cb7 = Cb7::<Rc4>::with_cipher();
+            cb7.beefcode(t.beefcode.0, t.beefcode.1);
+            let mut codes: Vec<(u32, u32)> = t.decrypted.iter().map(Code::as_tuple).collect();
+            for code in &mut codes {
+                let raw = *code;
+                cb7.encrypt_code_mut(&mut code.0, &mut code.1);
+                if is_beefcode(raw.0) {
+                    cb7.beefcode(raw.0, raw.1);
+                }
+            }
+            let result: Vec<Code> = codes.iter().map(|&code| code.into()).collect();
+            assert_eq!(result, t.encrypted);
+
+            // A mock cipher gives different results, which still round-trip
+            let mut enc = Cb7::<MockCipher>::with_cipher();
+            let mut dec = Cb7::<MockCipher>::with_cipher();
+            enc.beefcode(t.beefcode.0, t.beefcode.1);
+            dec.beefcode(t.beefcode.0, t.beefcode.1);
+            for (raw, expected) in t.decrypted.iter().zip(&t.encrypted) {
+                let code: Code = enc.encrypt_code(raw.0, raw.1).into();
+                assert_ne!(code, *expected);
+                let result: Code = dec.decrypt_code(code.0, code.1).into();
+                assert_eq!(result, *raw);
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_hash() {
+        use crate::std_alloc::HashSet;
+
+        let mut set = HashSet::new();
+        assert!(set.insert(Cb7::with_beefcode(BEEFCODE, 0)));
+        assert!(set.insert(Cb7::with_beefcode(BEEFCODE, 0xdead_face)));
+        assert!(!set.insert(Cb7::default()));
+        assert!(set.insert(Cb7::new()));
+        assert_eq!(set.len(), 3);
+    }
+
+    #[test]
+    fn test_with_rsa_params() {
+        for t in &tests() {
+            let mut cb7 = Cb7::with_rsa_params(RSA_ENC_KEY, RSA_DEC_KEY, RSA_MODULUS);
+            cb7.beefcode(t.beefcode.0, t.beefcode.1);
+
+            for (i, &code) in t.decrypted.iter().enumerate() {
+                let result: Code = cb7.encrypt_code(code.0, code.1).into();
+                assert_eq!(result, t.encrypted[i]);
+
+                if is_beefcode(code.0) {
+                    cb7.beefcode(code.0, code.1);
+                }
+            }
+        }
+
+        // Identity RSA
+        let mut cb7 = Cb7::with_rsa_params(1, 1, RSA_MODULUS);
+        cb7.beefcode(BEEFCODE, 0);
+        let mut dec = cb7;
+        for t in &tests() {
+            for &code in &t.decrypted[1..] {
+                let encrypted = cb7.encrypt_code(code.0, code.1);
+                let result: Code = dec.decrypt_code(encrypted.0, encrypted.1).into();
+                assert_eq!(result, code);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rsa_crypt_modulus() {
+        // Values >= modulus are passed through unchanged
+        for code in [(0xffff_ffff, 0xffff_fff5), (0xffff_ffff, 0xffff_ffff)] {
+            let (mut addr, mut val) = code;
+            rsa_crypt(&mut addr, &mut val, RSA_ENC_KEY, RSA_MODULUS);
+            assert_eq!((addr, val), code);
+            rsa_crypt(&mut addr, &mut val, RSA_DEC_KEY, RSA_MODULUS);
+            assert_eq!((addr, val), code);
+        }
+
+        // Values < modulus are transformed and restored
+        for code in [(0xffff_ffff, 0xffff_fff0), (0x2043_afcc, 0x2411_ffff)] {
+            let (mut addr, mut val) = code;
+            rsa_crypt(&mut addr, &mut val, RSA_ENC_KEY, RSA_MODULUS);
+            assert_ne!((addr, val), code);
+            rsa_crypt(&mut addr, &mut val, RSA_DEC_KEY, RSA_MODULUS);
+            assert_eq!((addr, val), code);
+        }
+    }
+
+    #[cfg(feature = "num-bigint")]
+    #[test]
+    fn test_modpow_cross_check() {
+        let bases = [
+            0,
+            1,
+            2,
+            0x2043_afcc_2411_ffff,
+            0xbeef_c0de_0000_0000,
+            RSA_MODULUS - 1,
+            RSA_MODULUS - 11,
+        ];
+        for base in bases {
+            for exp in [0, 1, 2, RSA_DEC_KEY, RSA_ENC_KEY, u64::MAX] {
+                for m in [2, 0xffff_ffff, RSA_MODULUS, u64::MAX] {
+                    assert_eq!(modpow_u128(base, exp, m), modpow_bigint(base, exp, m));
+                }
+            }
+        }
+
+        let mut x: u64 = 0x0123_4567_89ab_cdef;
+        for _ in 0..100 {
+            x = x.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            let base = x % RSA_MODULUS;
+            assert_eq!(
+                modpow_u128(base, RSA_ENC_KEY, RSA_MODULUS),
+                modpow_bigint(base, RSA_ENC_KEY, RSA_MODULUS)
+            );
+        }
+    }
+
+    #[test]
+    fn test_rsa_crypt_small() {
+        // Results with fewer than two 32-bit digits
+        for code in [(0, 0), (0, 1)] {
+            let (mut addr, mut val) = code;
+            rsa_crypt(&mut addr, &mut val, RSA_ENC_KEY, RSA_MODULUS);
+            assert_eq!((addr, val), code);
+            rsa_crypt(&mut addr, &mut val, RSA_DEC_KEY, RSA_MODULUS);
+            assert_eq!((addr, val), code);
+        }
+
+        let (mut addr, mut val) = (0, 2);
+        rsa_crypt(&mut addr, &mut val, 1, RSA_MODULUS);
+        assert_eq!((addr, val), (0, 2));
+    }
+
+    #[test]
+    fn test_reset() {
+        for t in &tests() {
+            let mut cb7 = Cb7::default();
+            for &code in &tests()[3].encrypted {
+                let result = cb7.decrypt_code(code.0, code.1);
+                if is_beefcode(result.0) {
+                    cb7.beefcode(result.0, result.1);
+                }
+            }
+            cb7.reset();
+            cb7.beefcode(t.beefcode.0, t.beefcode.1);
+
+            let mut fresh = Cb7::new();
+            fresh.beefcode(t.beefcode.0, t.beefcode.1);
+
+            assert_eq!(cb7.seeds, fresh.seeds);
+            assert_eq!(cb7.key, fresh.key);
+            assert_eq!(cb7.beefcodf, fresh.beefcodf);
+            assert_eq!(cb7.initialized, fresh.initialized);
+        }
+    }
+
+    #[test]
+    fn test_seeds_differ() {
+        let mut a = Cb7::new();
+        a.beefcode(BEEFCODE, 0);
+        let mut b = Cb7::new();
+        b.beefcode(BEEFCODE, 0xdead_face);
+        assert_eq!(a.seeds_differ(&a), None);
+        assert_eq!(a.seeds_differ(&b), Some((0, 0)));
+
+        let mut c = a;
+        c.seeds[3][42] ^= 1;
+        assert_eq!(a.seeds_differ(&c), Some((3, 42)));
+        assert_eq!(c.seeds_differ(&a), Some((3, 42)));
+    }
+
+    #[test]
+    fn test_default_const() {
+        const CB7: Cb7 = Cb7::default_const();
+        assert_eq!(CB7, Cb7::default());
+    }
+
+    #[test]
+    fn test_can_roundtrip() {
+        for t in &tests() {
+            let mut cb7 = Cb7::new();
+            cb7.beefcode(t.beefcode.0, t.beefcode.1);
+            for code in &t.decrypted {
+                assert!(cb7.can_roundtrip(code.0, code.1));
+            }
+        }
+
+        // Intermediate values at or above the modulus skip the RSA step, but
+        // still round-trip
+        let cb7 = Cb7::default();
+        for code in [(0xffff_ffff, 0xffff_fff5), (0xffff_ffff, 0xffff_ffff)] {
+            let (mut addr, mut val) = code;
+            rsa_crypt(&mut addr, &mut val, RSA_ENC_KEY, RSA_MODULUS);
+            assert_eq!((addr, val), code);
+            assert!(cb7.can_roundtrip(code.0, code.1));
+        }
+
+        // Non-inverse RSA keys break round-trips
+        let mut cb7 = Cb7::with_rsa_params(RSA_ENC_KEY, RSA_ENC_KEY, RSA_MODULUS);
+        cb7.beefcode(BEEFCODE, 0);
+        assert!(!cb7.can_roundtrip(0x2043_afcc, 0x2411_ffff));
+        let mut code = cb7.encrypt_code(0x2043_afcc, 0x2411_ffff);
+        cb7.decrypt_code_mut(&mut code.0, &mut code.1);
+        assert_ne!(code, (0x2043_afcc, 0x2411_ffff));
+    }
+
+    #[test]
+    fn test_encrypt_code_checked() {
+        let mut cb7 = Cb7::new();
+        assert_eq!(cb7.encrypt_code_checked(0x2043_afcc, 0x2411_ffff), Err(Uninitialized));
+        assert_eq!(cb7.encrypt_code_checked(BEEFCODE, 0), Err(Uninitialized));
+        assert_eq!(cb7, Cb7::new());
+
+        for t in &tests() {
+            let mut cb7 = Cb7::new();
+            cb7.beefcode(t.beefcode.0, t.beefcode.1);
+            for (i, &code) in t.decrypted.iter().enumerate() {
+                let result = cb7.encrypt_code_checked(code.0, code.1).map(Code::from);
+                assert_eq!(result, Ok(t.encrypted[i]));
+
+                if is_beefcode(code.0) {
+                    cb7.beefcode(code.0, code.1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_state_bytes() {
+        let mut beefcodf = Cb7::new();
+        beefcodf.beefcode(0xbeef_c0df, 0xb16b_00b5);
+        let states = [
+            Cb7::new(),
+            Cb7::default(),
+            Cb7::with_beefcode(BEEFCODE, 0xdead_face),
+            Cb7::with_rsa_params(3, 12_297_829_382_473_034_371, 18_446_744_073_709_551_557),
+            beefcodf,
+        ];
+        for cb7 in states {
+            let bytes = cb7.to_bytes();
+            assert_eq!(Cb7::from_bytes(&bytes), Ok(cb7));
+        }
+
+        let bytes = beefcodf.to_bytes();
+        assert_eq!(bytes[Cb7::STATE_LEN - 1], 0b11);
+        assert_eq!(bytes[SEEDS_LEN..SEEDS_LEN + 4], beefcodf.key[0].to_le_bytes());
+        assert_eq!(bytes[SEEDS_LEN + 36..SEEDS_LEN + 44], RSA_MODULUS.to_le_bytes());
+
+        assert_eq!(Cb7::from_bytes(&bytes[1..]), Err(StateError::Length(1324)));
+        assert_eq!(Cb7::from_bytes(&[]), Err(StateError::Length(0)));
+        let mut bytes = bytes;
+        bytes[Cb7::STATE_LEN - 1] = 0x04;
+        assert_eq!(Cb7::from_bytes(&bytes), Err(StateError::Flags(0x04)));
+    }
+
+    #[test]
+    fn test_supply_beefcodf_line() {
+        // BEEFC0DF
+        let t = &tests()[2];
+        let mut cb7 = Cb7::new();
+        cb7.beefcode(t.beefcode.0, t.beefcode.1);
+        assert_eq!(cb7.supply_beefcodf_line(t.decrypted[0].0, t.decrypted[0].1), Ok(()));
+        for (i, &code) in t.decrypted.iter().enumerate().skip(1) {
+            let result: Code = cb7.encrypt_code(code.0, code.1).into();
+            assert_eq!(result, t.encrypted[i]);
+        }
+        assert_eq!(cb7.supply_beefcodf_line(0, 0), Err(NotExpectingBeefcodf));
+
+        let mut cb7 = Cb7::default();
+        let before = cb7;
+        assert_eq!(cb7.supply_beefcodf_line(0, 0), Err(NotExpectingBeefcodf));
+        assert_eq!(cb7.seeds_differ(&before), None);
+    }
+
+    #[test]
+    fn test_begin_list() {
+        // List A ends with a BEEFC0DF code, list B is the default one
+        let a = &tests()[3];
+        let b = &tests()[0];
+
+        let mut cb7 = Cb7::new();
+        cb7.beefcode(a.beefcode.0, a.beefcode.1);
+        let result = cb7.decrypt_code(a.encrypted[0].0, a.encrypted[0].1);
+        cb7.beefcode(result.0, result.1);
+
+        let mut expected = cb7;
+        expected.beefcodf = false;
+
+        cb7.begin_list();
+        assert_eq!(cb7.finish(), Ok(()));
+        for code in &b.encrypted {
+            assert_eq!(cb7.decrypt_code(code.0, code.1), expected.decrypt_code(code.0, code.1));
+        }
+
+        // Concatenated complete lists are decrypted as if they were one
+        let mut cb7 = Cb7::new();
+        cb7.beefcode(b.beefcode.0, b.beefcode.1);
+        for _ in 0..2 {
+            cb7.begin_list();
+            for (i, &code) in b.encrypted.iter().enumerate() {
+                let result: Code = cb7.decrypt_code(code.0, code.1).into();
+                assert_eq!(result, b.decrypted[i]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_finish() {
+        for t in &tests() {
+            let mut cb7 = Cb7::new();
+            cb7.beefcode(t.beefcode.0, t.beefcode.1);
+
+            for code in &t.encrypted {
+                let result = cb7.decrypt_code(code.0, code.1);
+                if is_beefcode(result.0) {
+                    cb7.beefcode(result.0, result.1);
+                }
+            }
+            assert_eq!(cb7.finish(), Ok(()));
+        }
+
+        // BEEFC0DE & BEEFC0DF, truncated after BEEFC0DF
+        let t = &tests()[3];
+        let mut cb7 = Cb7::new();
+        cb7.beefcode(t.beefcode.0, t.beefcode.1);
+        let result = cb7.decrypt_code(t.encrypted[0].0, t.encrypted[0].1);
+        cb7.beefcode(result.0, result.1);
+        assert_eq!(cb7.finish(), Err(DanglingBeefcodf));
+    }
+
     #[test]
     fn test_decrypt_code_mut() {
         for t in &mut tests() {
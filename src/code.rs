@@ -0,0 +1,1203 @@
+//! A typed representation of a single cheat code.
+
+use core::fmt;
+use core::num::ParseIntError;
+use core::ops::BitXor;
+use core::str::FromStr;
+
+use crate::{cb1, cb7, decode_command, Width, V7_HEADER};
+
+#[cfg(feature = "alloc")]
+use alloc::{slice, string::String, vec::Vec};
+#[cfg(feature = "alloc")]
+use core::ops::{Index, IndexMut};
+
+/// A cheat code consisting of an address and a value.
+///
+/// # Example
+/// ```
+/// use codebreaker::Code;
+///
+/// let code = Code(0x2043AFCC, 0x2411FFFF);
+/// assert_eq!(code.to_string(), "2043AFCC 2411FFFF");
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Code(pub u32, pub u32);
+
+impl Code {
+    /// Returns the code as an `(address, value)` tuple.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Code, Codebreaker};
+    ///
+    /// let code = Code(0x2043AFCC, 0x2411FFFF);
+    /// let (addr, val) = code.as_tuple();
+    /// let mut cb = Codebreaker::new();
+    /// assert_eq!(cb.encrypt_code(addr, val), (0x2AFF014C, 0x2411FFFF));
+    /// ```
+    pub const fn as_tuple(&self) -> (u32, u32) {
+        (self.0, self.1)
+    }
+
+    /// Parses a code listed as `value address` instead of `address value`, as
+    /// exported by some tools. Otherwise accepts the same input as the
+    /// [`FromStr`] impl.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Code;
+    ///
+    /// let code = Code::parse_swapped("2411FFFF 2043AFCC").unwrap();
+    /// assert_eq!(code, Code(0x2043AFCC, 0x2411FFFF));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CodeParseError`] if the input is not a valid code.
+    pub fn parse_swapped(s: &str) -> Result<Self, CodeParseError> {
+        let code: Self = s.parse()?;
+        Ok(Self(code.1, code.0))
+    }
+
+    /// Returns true if the code is a beefcode (`BEEFC0DE` or `BEEFC0DF`).
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Code;
+    ///
+    /// assert!(Code(0xBEEFC0DE, 0x00000000).is_beefcode());
+    /// assert!(Code(0xBEEFC0DF, 0xB16B00B5).is_beefcode());
+    /// assert!(!Code(0x2043AFCC, 0x2411FFFF).is_beefcode());
+    /// ```
+    pub const fn is_beefcode(&self) -> bool {
+        cb7::is_beefcode(self.0)
+    }
+
+    /// Returns true if the code is the default v7 header `B4336FA9 4DFEFB79`,
+    /// which is `BEEFC0DE 00000000` encrypted with CB v1.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Code;
+    ///
+    /// assert!(Code(0xB4336FA9, 0x4DFEFB79).is_v7_header());
+    /// assert!(!Code(0xBEEFC0DE, 0x00000000).is_v7_header());
+    /// ```
+    pub const fn is_v7_header(&self) -> bool {
+        self.0 == V7_HEADER.0 && self.1 == V7_HEADER.1
+    }
+
+    /// Returns the width of a constant write code (commands `0` - `2`), or
+    /// `None` for any other command.
+    ///
+    /// The code is expected to be decrypted.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Code, Width};
+    ///
+    /// assert_eq!(Code(0x1031A028, 0x0000FFFF).write_width(), Some(Width::Bits16));
+    /// assert_eq!(Code(0x9029BEAC, 0x0C0A9225).write_width(), None);
+    /// ```
+    pub const fn write_width(&self) -> Option<Width> {
+        match cb1::command(self.0) {
+            0x0 => Some(Width::Bits8),
+            0x1 => Some(Width::Bits16),
+            0x2 => Some(Width::Bits32),
+            _ => None,
+        }
+    }
+
+    /// Returns a code that writes an 8-bit value to the given address.
+    ///
+    /// Only the lower 28 bits of the address are used.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Code;
+    ///
+    /// assert_eq!(Code::write8(0x0031789A, 0x63), Code(0x0031789A, 0x00000063));
+    /// ```
+    pub const fn write8(addr: u32, val: u8) -> Self {
+        Self(addr & 0x0fff_ffff, val as u32)
+    }
+
+    /// Returns a code that writes a 16-bit value to the given address.
+    ///
+    /// Only the lower 28 bits of the address are used.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Code;
+    ///
+    /// assert_eq!(Code::write16(0x0031789A, 0x0063), Code(0x1031789A, 0x00000063));
+    /// ```
+    pub const fn write16(addr: u32, val: u16) -> Self {
+        Self(0x1000_0000 | (addr & 0x0fff_ffff), val as u32)
+    }
+
+    /// Returns a code that writes a 32-bit value to the given address.
+    ///
+    /// Only the lower 28 bits of the address are used.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Code;
+    ///
+    /// assert_eq!(Code::write32(0x0043AFCC, 0x2411FFFF), Code(0x2043AFCC, 0x2411FFFF));
+    /// ```
+    pub const fn write32(addr: u32, val: u32) -> Self {
+        Self(0x2000_0000 | (addr & 0x0fff_ffff), val)
+    }
+
+    /// Returns a code with the same address and the value transformed by `f`.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Code;
+    ///
+    /// let code = Code(0x2096F5B8, 0x000000BE);
+    /// assert_eq!(code.map_val(|val| val + 1), Code(0x2096F5B8, 0x000000BF));
+    /// ```
+    pub fn map_val(&self, f: impl FnOnce(u32) -> u32) -> Self {
+        Self(self.0, f(self.1))
+    }
+
+    /// Returns the PS2 memory region targeted by the code, which should be
+    /// decrypted.
+    ///
+    /// The target address is the one reported by
+    /// [`decode_command`](crate::decode_command).
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Code, MemRegion};
+    ///
+    /// assert_eq!(Code(0x2043AFCC, 0x2411FFFF).addr_region(), MemRegion::MainRam);
+    /// assert_eq!(Code(0x2A000000, 0x00000000).addr_region(), MemRegion::Other);
+    /// assert_eq!(Code(0xBEEFC0DE, 0x00000000).addr_region(), MemRegion::Unknown);
+    /// ```
+    pub const fn addr_region(&self) -> MemRegion {
+        if self.0 == 0xffff_ffff || matches!(cb1::command(self.0), 0x8 | 0xa | 0xb) {
+            return MemRegion::Unknown;
+        }
+        if decode_command(self.0, self.1).address < MAIN_RAM_SIZE {
+            MemRegion::MainRam
+        } else {
+            MemRegion::Other
+        }
+    }
+
+    /// Formats the code like [`Display`](fmt::Display) into the given buffer
+    /// and returns the result as a string slice, without allocating.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Code;
+    ///
+    /// let mut buf = [0; 17];
+    /// let code = Code(0x2043AFCC, 0x2411FFFF);
+    /// assert_eq!(code.write_fmt_bytes(&mut buf), "2043AFCC 2411FFFF");
+    /// ```
+    pub const fn write_fmt_bytes<'a>(&self, buf: &'a mut [u8; 17]) -> &'a str {
+        const HEX: &[u8; 16] = b"0123456789ABCDEF";
+        let mut i = 0;
+        while i < 8 {
+            let shift = 28 - 4 * i;
+            buf[i] = HEX[((self.0 >> shift) & 0xf) as usize];
+            buf[9 + i] = HEX[((self.1 >> shift) & 0xf) as usize];
+            i += 1;
+        }
+        buf[8] = b' ';
+        match core::str::from_utf8(buf) {
+            Ok(s) => s,
+            Err(_) => unreachable!(),
+        }
+    }
+
+    // Parses a line from a list of cheats if it looks like a code, which is
+    // the case for exactly two words that each consist of 8 hex digits or
+    // have a 0x prefix. Returns None for any other line, e.g. a cheat name.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn parse_list_line(line: &str) -> Option<Result<Self, CodeParseError>> {
+        let is_code_word = |w: &str| {
+            (w.len() == 8 && w.bytes().all(|b| b.is_ascii_hexdigit())) || w.starts_with("0x") || w.starts_with("0X")
+        };
+        let mut words = line.split(is_separator).filter(|w| !w.is_empty());
+        match (words.next(), words.next(), words.next()) {
+            (Some(addr), Some(val), None) if is_code_word(addr) && is_code_word(val) => {
+                Some(parse_word(addr).and_then(|addr| Ok(Self(addr, parse_word(val)?))))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the code as 16 uppercase hex digits without a space, as
+    /// expected by some devices. [`Display`](fmt::Display) gives the spaced
+    /// form.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Code;
+    ///
+    /// let code = Code(0x2043AFCC, 0x2411FFFF);
+    /// assert_eq!(code.to_compact(), "2043AFCC2411FFFF");
+    /// assert_eq!(code.to_compact().parse(), Ok(code));
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn to_compact(&self) -> String {
+        alloc::format!("{:08X}{:08X}", self.0, self.1)
+    }
+
+    /// Returns the code in the text format of Action Replay and GameShark,
+    /// which is the same as [`Display`](fmt::Display): two 8-digit uppercase
+    /// hex words separated by a single space.
+    ///
+    /// Raw codes for these devices use the same numbers as CodeBreaker, so
+    /// only the text needs to follow their conventions. See
+    /// [`from_action_replay_str`](#method.from_action_replay_str) for the
+    /// variations found in exported lists.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Code;
+    ///
+    /// let code = Code(0x0031789A, 0x00000063);
+    /// assert_eq!(code.to_action_replay_string(), "0031789A 00000063");
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn to_action_replay_string(&self) -> String {
+        alloc::format!("{self}")
+    }
+
+    /// Parses a code in the text format of Action Replay and GameShark.
+    ///
+    /// Compared to [`to_action_replay_string`](#method.to_action_replay_string),
+    /// lowercase digits and any whitespace between and around the words, such
+    /// as tabs or multiple spaces, are accepted. Unlike the [`FromStr`] impl,
+    /// both words must have exactly 8 digits, and `0x` prefixes, other
+    /// separators, and the compact 16-digit form are rejected, as they are not
+    /// used by these devices.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Code, CodeParseError};
+    ///
+    /// let code = Code::from_action_replay_str("0031789a\t00000063").unwrap();
+    /// assert_eq!(code, Code(0x0031789A, 0x00000063));
+    ///
+    /// let err = Code::from_action_replay_str("31789A 63").unwrap_err();
+    /// assert_eq!(err, CodeParseError::InvalidWord);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CodeParseError::WordCount`] if the input does not consist of
+    /// two words, and [`CodeParseError::InvalidWord`] if a word is not 8 hex
+    /// digits.
+    pub fn from_action_replay_str(s: &str) -> Result<Self, CodeParseError> {
+        let parse = |w: &str| {
+            if w.len() != 8 || !w.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(CodeParseError::InvalidWord);
+            }
+            parse_word(w)
+        };
+        let mut words = s.split_whitespace();
+        match (words.next(), words.next(), words.next()) {
+            (Some(addr), Some(val), None) => Ok(Self(parse(addr)?, parse(val)?)),
+            _ => Err(CodeParseError::WordCount),
+        }
+    }
+}
+
+/// The PS2 memory region targeted by a code, as returned by
+/// [`Code::addr_region`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemRegion {
+    /// The 32 MB of main RAM, `00000000` - `01FFFFFF`.
+    MainRam,
+    /// An address outside of main RAM.
+    Other,
+    /// The code does not target memory, such as a beefcode or a code delay,
+    /// or its command is unknown.
+    Unknown,
+}
+
+const MAIN_RAM_SIZE: u32 = 0x0200_0000;
+
+impl From<(u32, u32)> for Code {
+    fn from(t: (u32, u32)) -> Self {
+        Self(t.0, t.1)
+    }
+}
+
+/// # Example
+/// ```
+/// use codebreaker::Code;
+///
+/// let codes = [[0x2043AFCC, 0x2411FFFF], [0x201F6024, 0x00000000]];
+/// assert_eq!(Code::from(codes[0]), Code(0x2043AFCC, 0x2411FFFF));
+/// assert_eq!(Code::from(&codes[1]), Code(0x201F6024, 0x00000000));
+/// ```
+impl From<[u32; 2]> for Code {
+    fn from(a: [u32; 2]) -> Self {
+        Self(a[0], a[1])
+    }
+}
+
+impl From<&[u32; 2]> for Code {
+    fn from(a: &[u32; 2]) -> Self {
+        Self(a[0], a[1])
+    }
+}
+
+/// # Example
+/// ```
+/// use codebreaker::Code;
+///
+/// let t: (u32, u32) = Code(0x2043AFCC, 0x2411FFFF).into();
+/// assert_eq!(t, (0x2043AFCC, 0x2411FFFF));
+/// ```
+impl From<Code> for (u32, u32) {
+    fn from(code: Code) -> Self {
+        code.as_tuple()
+    }
+}
+
+/// XORs the address and value with the given mask, e.g. to toggle bits.
+///
+/// # Example
+/// ```
+/// use codebreaker::Code;
+///
+/// let code = Code(0x0031789A, 0x00000063);
+/// assert_eq!(code ^ (0, 0x80), Code(0x0031789A, 0x000000E3));
+/// assert_eq!(code ^ (0x10000000, 0), Code(0x1031789A, 0x00000063));
+/// ```
+impl BitXor<(u32, u32)> for Code {
+    type Output = Self;
+
+    fn bitxor(self, mask: (u32, u32)) -> Self {
+        Self(self.0 ^ mask.0, self.1 ^ mask.1)
+    }
+}
+
+/// Parses a code from two hexadecimal words separated by whitespace, `-`, or
+/// `,`.
+///
+/// The words are split at the first separator, which may be surrounded by
+/// whitespace. Leading, trailing, and repeated separators like `,,` are
+/// rejected, while surrounding whitespace is ignored.
+///
+/// Words may have fewer than 8 digits, as found in some old code lists, and
+/// an optional `0x` prefix.
+///
+/// A single token of 16 hex digits without separator is split into two words
+/// of 8 digits each.
+///
+/// # Example
+/// ```
+/// use codebreaker::Code;
+///
+/// let code: Code = "31789A 63".parse().unwrap();
+/// assert_eq!(code, Code(0x0031789A, 0x00000063));
+/// assert_eq!(code.to_string(), "0031789A 00000063");
+///
+/// let code: Code = "0xB4336FA9 0x4DFEFB79".parse().unwrap();
+/// assert_eq!(code, Code(0xB4336FA9, 0x4DFEFB79));
+///
+/// let code: Code = "2043AFCC-2411FFFF".parse().unwrap();
+/// assert_eq!(code.to_string(), "2043AFCC 2411FFFF");
+///
+/// let code: Code = "2043AFCC2411FFFF".parse().unwrap();
+/// assert_eq!(code, Code(0x2043AFCC, 0x2411FFFF));
+///
+/// // Hex digits are case-insensitive, output is always uppercase
+/// let code: Code = "2043afcc 2411FfFf".parse().unwrap();
+/// assert_eq!(code.to_string(), "2043AFCC 2411FFFF");
+/// ```
+impl FromStr for Code {
+    type Err = CodeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let Some(pos) = s.find(is_separator) else {
+            if s.len() != 16 {
+                return Err(CodeParseError::WordCount);
+            }
+            if !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(CodeParseError::InvalidWord);
+            }
+            let (addr, val) = s.split_at(8);
+            return Ok(Self(parse_word(addr)?, parse_word(val)?));
+        };
+
+        // Split once at the first separator, allowing whitespace around it
+        let (addr, rest) = s.split_at(pos);
+        let rest = rest.trim_start();
+        let val = rest.strip_prefix(['-', ',']).map_or(rest, str::trim_start);
+        if addr.is_empty() || val.is_empty() || val.contains(is_separator) {
+            return Err(CodeParseError::WordCount);
+        }
+        Ok(Self(parse_word(addr)?, parse_word(val)?))
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '-' | ',')
+}
+
+fn parse_word(s: &str) -> Result<u32, CodeParseError> {
+    let s = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")).unwrap_or(s);
+    // from_str_radix accepts a sign and leading zeros beyond 8 digits
+    if s.is_empty() || s.len() > 8 || s.starts_with('+') {
+        return Err(CodeParseError::InvalidWord);
+    }
+    Ok(u32::from_str_radix(s, 16)?)
+}
+
+#[cfg(test)]
+impl From<&str> for Code {
+    fn from(s: &str) -> Self {
+        s.parse().expect("invalid code format")
+    }
+}
+
+// Implements ToString
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:08X} {:08X}", self.0, self.1)
+    }
+}
+
+/// Formats both words as 8 lowercase hex digits, with `0x` prefixes if the
+/// `#` flag is given.
+///
+/// # Example
+/// ```
+/// use codebreaker::Code;
+///
+/// let code = Code(0x2043AFCC, 0x2411FFFF);
+/// assert_eq!(format!("{code:x}"), "2043afcc 2411ffff");
+/// assert_eq!(format!("{code:#x}"), "0x2043afcc 0x2411ffff");
+/// ```
+impl fmt::LowerHex for Code {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{:#010x} {:#010x}", self.0, self.1)
+        } else {
+            write!(f, "{:08x} {:08x}", self.0, self.1)
+        }
+    }
+}
+
+/// Formats both words as 8 uppercase hex digits, with `0x` prefixes if the
+/// `#` flag is given.
+///
+/// # Example
+/// ```
+/// use codebreaker::Code;
+///
+/// let code = Code(0x2043AFCC, 0x2411FFFF);
+/// assert_eq!(format!("{code:X}"), "2043AFCC 2411FFFF");
+/// assert_eq!(format!("{code:#X}"), "0x2043AFCC 0x2411FFFF");
+/// ```
+impl fmt::UpperHex for Code {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{:#010X} {:#010X}", self.0, self.1)
+        } else {
+            write!(f, "{:08X} {:08X}", self.0, self.1)
+        }
+    }
+}
+
+/// Formats the code like [`Display`](fmt::Display) for [`ufmt`], without the
+/// footprint of `core::fmt`.
+///
+/// Requires the "ufmt" feature.
+///
+/// # Example
+/// ```
+/// use codebreaker::Code;
+/// use core::convert::Infallible;
+///
+/// // A sink that works without std
+/// struct Buf {
+///     bytes: [u8; 32],
+///     len: usize,
+/// }
+///
+/// impl ufmt::uWrite for Buf {
+///     type Error = Infallible;
+///
+///     fn write_str(&mut self, s: &str) -> Result<(), Infallible> {
+///         self.bytes[self.len..self.len + s.len()].copy_from_slice(s.as_bytes());
+///         self.len += s.len();
+///         Ok(())
+///     }
+/// }
+///
+/// let mut buf = Buf { bytes: [0; 32], len: 0 };
+/// ufmt::uwrite!(buf, "{}", Code(0x2043AFCC, 0x2411FFFF)).unwrap();
+/// assert_eq!(&buf.bytes[..buf.len], b"2043AFCC 2411FFFF");
+/// ```
+#[cfg(feature = "ufmt")]
+impl ufmt::uDisplay for Code {
+    fn fmt<W: ufmt::uWrite + ?Sized>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error> {
+        let mut buf = [0; 17];
+        f.write_str(self.write_fmt_bytes(&mut buf))
+    }
+}
+
+// Used by assert_eq!
+impl fmt::Debug for Code {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+/// A list of codes.
+///
+/// # Example
+/// ```
+/// use codebreaker::{Code, CodeList};
+///
+/// let mut list = CodeList::new();
+/// list.push(Code(0x2043AFCC, 0x2411FFFF));
+/// list.push(Code(0x201F6024, 0x00000000));
+/// assert_eq!(list.len(), 2);
+/// assert_eq!(list[1], Code(0x201F6024, 0x00000000));
+///
+/// list[1].1 = 0x000000BE;
+/// assert_eq!(list.iter().last(), Some(&Code(0x201F6024, 0x000000BE)));
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeList(Vec<Code>);
+
+/// Does the same as [`new`](#method.new).
+#[cfg(feature = "alloc")]
+impl Default for CodeList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl CodeList {
+    /// Returns a new, empty list.
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Returns a new, empty list with room for at least `capacity` codes
+    /// without reallocating.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::CodeList;
+    ///
+    /// let list = CodeList::with_capacity(16);
+    /// assert!(list.is_empty());
+    /// assert!(list.capacity() >= 16);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(Vec::with_capacity(capacity))
+    }
+
+    /// Returns the number of codes the list can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Returns the number of codes in the list.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if the list contains no codes.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an iterator over the codes.
+    pub fn iter(&self) -> slice::Iter<'_, Code> {
+        self.0.iter()
+    }
+
+    /// Appends a code to the end of the list.
+    pub fn push(&mut self, code: Code) {
+        self.0.push(code);
+    }
+
+    /// Writes the codes to `w`, each on its own line, without allocating.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Code, CodeList};
+    ///
+    /// let mut list = CodeList::new();
+    /// list.push(Code(0x2043AFCC, 0x2411FFFF));
+    /// list.push(Code(0x201F6024, 0x00000000));
+    ///
+    /// let mut s = String::new();
+    /// list.write_to(&mut s).unwrap();
+    /// assert_eq!(s, "2043AFCC 2411FFFF\n201F6024 00000000\n");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to `w` fails.
+    pub fn write_to(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        self.iter().try_for_each(|code| writeln!(w, "{code}"))
+    }
+
+    /// Parses a list with one code per line, like the [`FromStr`] impl, but
+    /// without stripping leading line numbers or bullets. Lines starting with
+    /// such a marker, including a `-` bullet, are rejected.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{CodeList, CodeParseError};
+    ///
+    /// let list = CodeList::parse_strict("2043AFCC 2411FFFF\n201F6024 00000000").unwrap();
+    /// assert_eq!(list.len(), 2);
+    ///
+    /// let err = CodeList::parse_strict("1. 2043AFCC 2411FFFF").unwrap_err();
+    /// assert_eq!(err, CodeParseError::WordCount);
+    ///
+    /// let err = CodeList::parse_strict("- 2043AFCC 2411FFFF").unwrap_err();
+    /// assert_eq!(err, CodeParseError::WordCount);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CodeParseError`] for the first line that is not a valid code.
+    pub fn parse_strict(s: &str) -> Result<Self, CodeParseError> {
+        Self::parse_lines(s, |line| line)
+    }
+
+    fn parse_lines(s: &str, f: impl Fn(&str) -> &str) -> Result<Self, CodeParseError> {
+        s.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| f(line).parse())
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+/// Parses a list with one code per line, skipping blank lines.
+///
+/// A leading line number like `1.` or a bullet like `-` or `*` is stripped
+/// from each line, as found in lists copied from forums. Use
+/// [`CodeList::parse_strict`] to reject such lines instead.
+///
+/// # Example
+/// ```
+/// use codebreaker::{Code, CodeList};
+///
+/// let list: CodeList = "1. 2043AFCC 2411FFFF\n2. 201F6024 00000000".parse().unwrap();
+/// assert_eq!(list[1], Code(0x201F6024, 0x00000000));
+///
+/// let list: CodeList = "* 2043AFCC 2411FFFF\n- 201F6024 00000000".parse().unwrap();
+/// assert_eq!(list[0], Code(0x2043AFCC, 0x2411FFFF));
+/// ```
+#[cfg(feature = "alloc")]
+impl FromStr for CodeList {
+    type Err = CodeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_lines(s, strip_list_marker)
+    }
+}
+
+// Strips a leading "1." or "-" / "*" followed by whitespace
+#[cfg(feature = "alloc")]
+fn strip_list_marker(line: &str) -> &str {
+    let rest = line.trim_start_matches(|c: char| c.is_ascii_digit());
+    let rest = if rest.len() < line.len() {
+        rest.strip_prefix('.')
+    } else {
+        rest.strip_prefix(['-', '*'])
+    };
+    match rest {
+        Some(rest) if rest.starts_with(char::is_whitespace) => rest.trim_start(),
+        _ => line,
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Index<usize> for CodeList {
+    type Output = Code;
+
+    fn index(&self, index: usize) -> &Code {
+        &self.0[index]
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl IndexMut<usize> for CodeList {
+    fn index_mut(&mut self, index: usize) -> &mut Code {
+        &mut self.0[index]
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> IntoIterator for &'a CodeList {
+    type Item = &'a Code;
+    type IntoIter = slice::Iter<'a, Code>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// # Example
+/// ```
+/// use codebreaker::{Code, CodeList};
+///
+/// let mut list = CodeList::new();
+/// list.extend([Code(0x9029BEAC, 0x0C0A9225), Code(0x201F6024, 0x00000000)]);
+/// assert_eq!(list.len(), 2);
+/// ```
+#[cfg(feature = "alloc")]
+impl Extend<Code> for CodeList {
+    fn extend<I: IntoIterator<Item = Code>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+/// # Example
+/// ```
+/// use codebreaker::{Code, CodeList};
+///
+/// let mut list = CodeList::new();
+/// list.extend([(0x9029BEAC, 0x0C0A9225), (0x201F6024, 0x00000000)]);
+/// assert_eq!(list[1], Code(0x201F6024, 0x00000000));
+/// ```
+#[cfg(feature = "alloc")]
+impl Extend<(u32, u32)> for CodeList {
+    fn extend<I: IntoIterator<Item = (u32, u32)>>(&mut self, iter: I) {
+        self.0.extend(iter.into_iter().map(Code::from));
+    }
+}
+
+/// A line of a cheat file, which is either a code or a comment.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Line {
+    /// A code.
+    Code(Code),
+    /// A comment, such as a cheat name, which is kept as is.
+    Comment(String),
+}
+
+/// The result of processing a line of text, as returned by
+/// [`Codebreaker::process_line`](crate::Codebreaker::process_line).
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineResult {
+    /// A code, which has been decrypted.
+    Code(Code),
+    /// A comment, such as a cheat name, without surrounding whitespace.
+    Comment(String),
+    /// An empty line or one with only whitespace.
+    Blank,
+    /// A line that looks like a code but could not be parsed.
+    ParseError(CodeParseError),
+}
+
+/// Error returned when parsing a [`Code`] from a string fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodeParseError {
+    /// The string does not consist of exactly two words.
+    WordCount,
+    /// A word is empty, has more than 8 digits, or is otherwise malformed.
+    InvalidWord,
+    /// A word contains characters that are not hex digits.
+    InvalidHex(ParseIntError),
+}
+
+impl fmt::Display for CodeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WordCount => f.write_str("expected address and value"),
+            Self::InvalidWord => f.write_str("expected hexadecimal number of up to 8 digits"),
+            Self::InvalidHex(_) => f.write_str("invalid hexadecimal number"),
+        }
+    }
+}
+
+/// # Example
+/// ```
+/// use codebreaker::{Code, CodeParseError};
+/// use core::error::Error;
+/// use core::num::{IntErrorKind, ParseIntError};
+///
+/// let err = "2043AFCG 2411FFFF".parse::<Code>().unwrap_err();
+/// assert!(matches!(err, CodeParseError::InvalidHex(_)));
+/// let source = err.source().and_then(|e| e.downcast_ref::<ParseIntError>()).unwrap();
+/// assert_eq!(source.kind(), &IntErrorKind::InvalidDigit);
+/// ```
+impl core::error::Error for CodeParseError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::InvalidHex(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseIntError> for CodeParseError {
+    fn from(err: ParseIntError) -> Self {
+        Self::InvalidHex(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "alloc")]
+    use crate::std_alloc::vec;
+    use crate::std_alloc::ToString;
+    use crate::{decode_command, Operation, Width};
+    #[cfg(feature = "std")]
+    use pretty_assertions::assert_eq;
+
+    fn invalid_digit() -> CodeParseError {
+        u32::from_str_radix("G", 16).unwrap_err().into()
+    }
+
+    #[test]
+    fn test_parse() {
+        let tests: &[(&str, Result<Code, CodeParseError>)] = &[
+            ("2043AFCC 2411FFFF", Ok(Code(0x2043_afcc, 0x2411_ffff))),
+            ("  2043AFCC\t2411FFFF ", Ok(Code(0x2043_afcc, 0x2411_ffff))),
+            ("31789A 63", Ok(Code(0x0031_789a, 0x0000_0063))),
+            ("0xB4336FA9 0X4DFEFB79", Ok(Code(0xb433_6fa9, 0x4dfe_fb79))),
+            ("2043AFCC-2411FFFF", Ok(Code(0x2043_afcc, 0x2411_ffff))),
+            ("2043AFCC,2411FFFF", Ok(Code(0x2043_afcc, 0x2411_ffff))),
+            ("2043AFCC, 2411FFFF", Ok(Code(0x2043_afcc, 0x2411_ffff))),
+            ("0x2043AFCC-0x2411FFFF", Ok(Code(0x2043_afcc, 0x2411_ffff))),
+            ("2043AFCC;2411FFFF", Err(CodeParseError::WordCount)),
+            ("2043AFCC-2411FFFF-0", Err(CodeParseError::WordCount)),
+            ("2043AFCC - 2411FFFF", Ok(Code(0x2043_afcc, 0x2411_ffff))),
+            (",2043AFCC,,2411FFFF,", Err(CodeParseError::WordCount)),
+            ("2043AFCC,,2411FFFF", Err(CodeParseError::WordCount)),
+            ("2043AFCC,-2411FFFF", Err(CodeParseError::WordCount)),
+            ("2043AFCC , , 2411FFFF", Err(CodeParseError::WordCount)),
+            ("-2043AFCC 2411FFFF", Err(CodeParseError::WordCount)),
+            (", 2043AFCC 2411FFFF", Err(CodeParseError::WordCount)),
+            ("2043AFCC 2411FFFF-", Err(CodeParseError::WordCount)),
+            ("2043AFCC 2411FFFF,", Err(CodeParseError::WordCount)),
+            ("2043AFCC-", Err(CodeParseError::WordCount)),
+            ("0x 4DFEFB79", Err(CodeParseError::InvalidWord)),
+            ("0x0xB4336FA 4DFEFB79", Err(CodeParseError::InvalidWord)),
+            ("2043AFCC2411FFFF", Ok(Code(0x2043_afcc, 0x2411_ffff))),
+            (" 2043afcc2411ffff\n", Ok(Code(0x2043_afcc, 0x2411_ffff))),
+            ("2043AFCC2411FFF", Err(CodeParseError::WordCount)),
+            ("2043AFCC2411FFFFF", Err(CodeParseError::WordCount)),
+            ("2043AFCC2411FFFG", Err(CodeParseError::InvalidWord)),
+            ("0x2043AFCC2411FF", Err(CodeParseError::InvalidWord)),
+            ("2043AFCC\u{e9}411FFF", Err(CodeParseError::InvalidWord)),
+            ("2043AFCC", Err(CodeParseError::WordCount)),
+            ("2043AFCC 2411FFFF 0", Err(CodeParseError::WordCount)),
+            ("", Err(CodeParseError::WordCount)),
+            ("2043AFCC0 2411FFFF", Err(CodeParseError::InvalidWord)),
+            ("2043AFCG 2411FFFF", Err(invalid_digit())),
+            ("2043AFCC 2411\u{e9}F", Err(invalid_digit())),
+            ("+2043AFC 2411FFFF", Err(CodeParseError::InvalidWord)),
+        ];
+        for t in tests {
+            assert_eq!(t.0.parse::<Code>(), t.1);
+        }
+    }
+
+    #[test]
+    fn test_parse_error_source() {
+        use core::error::Error;
+        use core::num::IntErrorKind;
+
+        let err = "2043AFCC 2411FFFG".parse::<Code>().unwrap_err();
+        let source = err.source().and_then(|e| e.downcast_ref::<ParseIntError>());
+        assert_eq!(source.map(ParseIntError::kind), Some(&IntErrorKind::InvalidDigit));
+
+        let err = "2043AFCC".parse::<Code>().unwrap_err();
+        assert!(err.source().is_none());
+    }
+
+    #[test]
+    fn test_parse_swapped() {
+        let tests = [
+            ("2411FFFF 2043AFCC", Code(0x2043_afcc, 0x2411_ffff)),
+            ("000000BE-2096F5B8", Code(0x2096_f5b8, 0x0000_00be)),
+            ("000000632031789A", Code(0x2031_789a, 0x0000_0063)),
+        ];
+        for (input, expected) in tests {
+            assert_eq!(Code::parse_swapped(input), Ok(expected));
+        }
+        assert_eq!(Code::parse_swapped("2411FFFF"), Err(CodeParseError::WordCount));
+        // The default order is unchanged
+        assert_eq!("2411FFFF 2043AFCC".parse(), Ok(Code(0x2411_ffff, 0x2043_afcc)));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_action_replay_roundtrip() {
+        let codes = [
+            Code(0, 0),
+            Code(0x0031_789a, 0x0000_0063),
+            Code(0x2043_afcc, 0x2411_ffff),
+            Code(0xffff_ffff, 0xffff_ffff),
+        ];
+        for code in codes {
+            let s = code.to_action_replay_string();
+            assert_eq!(Code::from_action_replay_str(&s), Ok(code));
+            assert_eq!(Code::from_action_replay_str(&s.to_lowercase()), Ok(code));
+        }
+    }
+
+    #[test]
+    fn test_from_action_replay_str() {
+        let tests = [
+            ("2043AFCC 2411FFFF", Ok(Code(0x2043_afcc, 0x2411_ffff))),
+            (" 2043afcc\t 2411ffff\n", Ok(Code(0x2043_afcc, 0x2411_ffff))),
+            ("2043AFCC", Err(CodeParseError::WordCount)),
+            ("2043AFCC 2411FFFF 0", Err(CodeParseError::WordCount)),
+            ("2043AFCC2411FFFF", Err(CodeParseError::WordCount)),
+            ("2043AFCC-2411FFFF", Err(CodeParseError::WordCount)),
+            ("31789A 63", Err(CodeParseError::InvalidWord)),
+            ("0x2043AFCC 2411FFFF", Err(CodeParseError::InvalidWord)),
+            ("2043AFCG 2411FFFF", Err(CodeParseError::InvalidWord)),
+            ("+043AFCC 2411FFFF", Err(CodeParseError::InvalidWord)),
+        ];
+        for (input, expected) in tests {
+            assert_eq!(Code::from_action_replay_str(input), expected);
+        }
+    }
+
+    #[test]
+    fn test_parse_case() {
+        let upper: Code = "2043AFCC 2411FFFF".parse().unwrap();
+        for s in [
+            "2043afcc 2411ffff",
+            "2043aFcC 2411FfFf",
+            "0x2043afcc 0X2411FFFF",
+            "2043afcc2411ffff",
+        ] {
+            let code: Code = s.parse().unwrap();
+            assert_eq!(code, upper);
+            assert_eq!(code.to_string(), "2043AFCC 2411FFFF");
+        }
+    }
+
+    #[test]
+    fn test_write_width() {
+        let tests: &[(Code, Option<Width>)] = &[
+            ("0031789A 00000063".into(), Some(Width::Bits8)),
+            ("1031A028 0000FFFF".into(), Some(Width::Bits16)),
+            ("201F6024 00000000".into(), Some(Width::Bits32)),
+            ("30400000 0031789A".into(), None),
+            ("9029BEAC 0C0A9225".into(), None),
+            ("D031789A 00000063".into(), None),
+            ("BEEFC0DE 00000000".into(), None),
+        ];
+        for &(code, width) in tests {
+            assert_eq!(code.write_width(), width);
+        }
+    }
+
+    #[test]
+    fn test_write() {
+        let tests = [
+            (Code::write8(0x0031_789a, 0x63), Width::Bits8, 0x63),
+            (Code::write8(0xf031_789a, 0xff), Width::Bits8, 0xff),
+            (Code::write16(0x0031_789a, 0xbeef), Width::Bits16, 0xbeef),
+            (Code::write32(0x0043_afcc, 0x2411_ffff), Width::Bits32, 0x2411_ffff),
+        ];
+        for (code, width, value) in tests {
+            let cmd = decode_command(code.0, code.1);
+            assert_eq!(cmd.address, code.0 & 0x0fff_ffff);
+            assert_eq!(cmd.operation, Operation::Write { width, value });
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_code_list() {
+        let mut list = CodeList::new();
+        assert!(list.is_empty());
+
+        list.push("9029BEAC 0C0A9225".into());
+        list.push("201F6024 00000000".into());
+        assert!(!list.is_empty());
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0], "9029BEAC 0C0A9225".into());
+
+        list[0] = "2096F5B8 000000BE".into();
+        let codes: Vec<Code> = list.iter().copied().collect();
+        assert_eq!(codes, ["2096F5B8 000000BE".into(), "201F6024 00000000".into()]);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_code_list_write_to() {
+        let mut s = String::new();
+        CodeList::new().write_to(&mut s).unwrap();
+        assert_eq!(s, "");
+
+        let list: CodeList = "2043AFCC 2411FFFF\n31789A 63".parse().unwrap();
+        list.write_to(&mut s).unwrap();
+        assert_eq!(s, "2043AFCC 2411FFFF\n0031789A 00000063\n");
+        assert_eq!(s.parse(), Ok(list));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_code_list_parse() {
+        let expected = vec![Code(0x2043AFCC, 0x2411FFFF), Code(0xB4336FA9, 0x4DFEFB79)];
+        let inputs = [
+            "2043AFCC 2411FFFF\nB4336FA9 4DFEFB79",
+            "1. 2043AFCC 2411FFFF\n2. B4336FA9 4DFEFB79",
+            "  9.  2043AFCC 2411FFFF\n\n  10.\tB4336FA9 4DFEFB79\n",
+            "- 2043AFCC 2411FFFF\n- B4336FA9 4DFEFB79",
+            "* 2043AFCC 2411FFFF\n* B4336FA9 4DFEFB79",
+        ];
+        for input in inputs {
+            let list: CodeList = input.parse().unwrap();
+            assert_eq!(list, CodeList(expected.clone()));
+        }
+
+        // A marker must be followed by whitespace
+        assert_eq!(
+            "1.2043AFCC 2411FFFF".parse::<CodeList>(),
+            Err(CodeParseError::InvalidWord)
+        );
+        assert_eq!("* 2043AFCC".parse::<CodeList>(), Err(CodeParseError::WordCount));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_code_list_parse_strict() {
+        let list = CodeList::parse_strict("2043AFCC 2411FFFF\n\nB4336FA9 4DFEFB79").unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(
+            CodeList::parse_strict("1. 2043AFCC 2411FFFF"),
+            Err(CodeParseError::WordCount)
+        );
+        assert_eq!(
+            CodeList::parse_strict("* 2043AFCC 2411FFFF"),
+            Err(CodeParseError::WordCount)
+        );
+        assert_eq!(
+            CodeList::parse_strict("- 2043AFCC 2411FFFF"),
+            Err(CodeParseError::WordCount)
+        );
+        assert_eq!(
+            CodeList::parse_strict("2043AFCC 2411FFFF\n-201F6024 00000000"),
+            Err(CodeParseError::WordCount)
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_code_list_extend() {
+        let mut list = CodeList::new();
+        list.extend([Code(0x9029_beac, 0x0c0a_9225)]);
+        list.extend(vec![(0x201f_6024, 0x0000_0000), (0x2096_f5b8, 0x0000_00be)]);
+        list.extend(core::iter::empty::<Code>());
+
+        let codes: Vec<Code> = list.iter().copied().collect();
+        assert_eq!(
+            codes,
+            [
+                "9029BEAC 0C0A9225".into(),
+                "201F6024 00000000".into(),
+                "2096F5B8 000000BE".into()
+            ]
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_code_list_default_with_capacity() {
+        let mut list = CodeList::default();
+        assert_eq!(list, CodeList::new());
+        list.push(Code(0x2043_afcc, 0x2411_ffff));
+        assert_eq!(list.len(), 1);
+
+        let mut list = CodeList::with_capacity(2);
+        assert!(list.is_empty());
+        assert!(list.capacity() >= 2);
+        list.extend([(0x2043_afcc, 0x2411_ffff), (0x201f_6024, 0x0000_0000)]);
+        assert_eq!(list.len(), 2);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    #[should_panic]
+    fn test_code_list_index_out_of_bounds() {
+        let list = CodeList::new();
+        let _ = list[0];
+    }
+
+    #[test]
+    fn test_addr_region() {
+        let tests = [
+            ("0031789A 00000063", MemRegion::MainRam),
+            ("201F6024 00000000", MemRegion::MainRam),
+            ("21FFFFFC 00000000", MemRegion::MainRam),
+            ("22000000 00000000", MemRegion::Other),
+            ("9029BEAC 0C0A9225", MemRegion::MainRam),
+            ("D031789A 00000063", MemRegion::MainRam),
+            // Multi-line conditional with address in value
+            ("E0020063 0031789A", MemRegion::MainRam),
+            ("E0020063 0A000000", MemRegion::Other),
+            ("B0000000 00000010", MemRegion::Unknown),
+            ("8031789A 00000063", MemRegion::Unknown),
+            ("BEEFC0DE 00000000", MemRegion::Unknown),
+            ("FFFFFFFF 000F0001", MemRegion::Unknown),
+        ];
+        for (code, region) in tests {
+            assert_eq!(Code::from(code).addr_region(), region);
+        }
+    }
+
+    #[test]
+    fn test_write_fmt_bytes() {
+        let codes = [
+            Code(0, 0),
+            Code(0x2043_afcc, 0x2411_ffff),
+            Code(0x0031_789a, 0x0000_0063),
+            Code(0xffff_ffff, 0xffff_ffff),
+        ];
+        for code in codes {
+            let mut buf = [0; 17];
+            assert_eq!(code.write_fmt_bytes(&mut buf), code.to_string());
+        }
+    }
+
+    #[cfg(all(feature = "ufmt", feature = "std"))]
+    #[test]
+    fn test_ufmt() {
+        let codes = [
+            (Code(0, 0), "00000000 00000000"),
+            (Code(0x2043_afcc, 0x2411_ffff), "2043AFCC 2411FFFF"),
+            (Code(0x0031_789a, 0x0000_0063), "0031789A 00000063"),
+        ];
+        for (code, expected) in codes {
+            let mut s = String::new();
+            ufmt::uwrite!(s, "{}", code).unwrap();
+            assert_eq!(s, expected);
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let code: Code = "31789A 63".into();
+        assert_eq!(code.to_string(), "0031789A 00000063");
+    }
+}
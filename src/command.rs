@@ -0,0 +1,311 @@
+//! Decoding of the CodeBreaker command set.
+
+use crate::cb1;
+
+/// All command nibbles (upper 4 bits of the code address) with a short
+/// description of what they do.
+///
+/// # Example
+/// ```
+/// use codebreaker::COMMANDS;
+///
+/// assert_eq!(COMMANDS[0x2], (0x2, "32-bit constant write"));
+/// ```
+pub const COMMANDS: &[(u8, &str)] = &[
+    (0x0, "8-bit constant write"),
+    (0x1, "16-bit constant write"),
+    (0x2, "32-bit constant write"),
+    (0x3, "increment/decrement"),
+    (0x4, "32-bit multi-address write"),
+    (0x5, "copy bytes"),
+    (0x6, "pointer write"),
+    (0x7, "bitwise operation"),
+    (0x8, "unused"),
+    (0x9, "master code (hook)"),
+    (0xa, "unused"),
+    (0xb, "code delay"),
+    (0xc, "32-bit test, execute all following codes"),
+    (0xd, "16/8-bit test, execute next code"),
+    (0xe, "16/8-bit test, execute next n codes"),
+    (0xf, "master code (hook)"),
+];
+
+/// A code broken down into its command, target address, and operation, as
+/// returned by [`decode_command`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodedCommand {
+    /// The command nibble (upper 4 bits of the code address).
+    pub command: u8,
+    /// The target address (lower 28 bits of the code address, or of the code
+    /// value for multi-line conditionals).
+    pub address: u32,
+    /// What the command does.
+    pub operation: Operation,
+}
+
+/// The operation performed by a [`DecodedCommand`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// Writes a constant value to the target address.
+    Write {
+        /// The width of the write.
+        width: Width,
+        /// The value to write.
+        value: u32,
+    },
+    /// Compares the value at the target address and executes other codes
+    /// only if the comparison holds.
+    Conditional {
+        /// The width of the comparison.
+        width: Width,
+        /// The comparison to perform.
+        comparison: Comparison,
+        /// The value to compare with.
+        value: u32,
+        /// The number of following codes that depend on the comparison, or
+        /// `None` for all following codes.
+        lines: Option<u8>,
+    },
+    /// Any other command, which is not decoded (yet).
+    Unknown,
+}
+
+/// The width of a memory access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Width {
+    /// 8 bits.
+    Bits8,
+    /// 16 bits.
+    Bits16,
+    /// 32 bits.
+    Bits32,
+}
+
+/// The comparison performed by a conditional code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    /// Memory is equal to the value.
+    Equal,
+    /// Memory is not equal to the value.
+    NotEqual,
+    /// Memory is less than the value.
+    LessThan,
+    /// Memory is greater than the value.
+    GreaterThan,
+    /// Memory AND the value is zero.
+    Nand,
+    /// Memory AND the value is non-zero.
+    And,
+    /// Memory OR the value is not all ones.
+    Nor,
+    /// Memory OR the value is all ones.
+    Or,
+}
+
+impl Comparison {
+    const fn from_bits(bits: u32) -> Option<Self> {
+        match bits {
+            0 => Some(Self::Equal),
+            1 => Some(Self::NotEqual),
+            2 => Some(Self::LessThan),
+            3 => Some(Self::GreaterThan),
+            4 => Some(Self::Nand),
+            5 => Some(Self::And),
+            6 => Some(Self::Nor),
+            7 => Some(Self::Or),
+            _ => None,
+        }
+    }
+}
+
+/// Decodes a raw (decrypted) code into its command structure.
+///
+/// Supported are the constant writes (`0` - `2`) and the conditionals
+/// (`C` - `E`):
+///
+/// ```text
+/// 0aaaaaaa 000000vv    8-bit write
+/// 1aaaaaaa 0000vvvv    16-bit write
+/// 2aaaaaaa vvvvvvvv    32-bit write
+/// Caaaaaaa vvvvvvvv    32-bit equal, execute all following codes
+/// Daaaaaaa 00t0vvvv    16-bit test, execute next code
+/// Daaaaaaa 01t000vv    8-bit test, execute next code
+/// E0nnvvvv taaaaaaa    16-bit test, execute next n codes
+/// E1nn00vv taaaaaaa    8-bit test, execute next n codes
+///
+/// t = 0: equal, 1: not equal, 2: less than, 3: greater than,
+///     4: NAND, 5: AND, 6: NOR, 7: OR
+/// ```
+///
+/// All other commands decode to [`Operation::Unknown`].
+///
+/// # Example
+/// ```
+/// use codebreaker::{decode_command, Comparison, Operation, Width};
+///
+/// let cmd = decode_command(0x2043AFCC, 0x2411FFFF);
+/// assert_eq!(cmd.command, 0x2);
+/// assert_eq!(cmd.address, 0x0043AFCC);
+/// assert_eq!(cmd.operation, Operation::Write { width: Width::Bits32, value: 0x2411FFFF });
+///
+/// let cmd = decode_command(0xE1020063, 0x1031789A);
+/// assert_eq!(cmd.address, 0x0031789A);
+/// assert_eq!(
+///     cmd.operation,
+///     Operation::Conditional {
+///         width: Width::Bits8,
+///         comparison: Comparison::NotEqual,
+///         value: 0x63,
+///         lines: Some(2),
+///     }
+/// );
+/// ```
+pub const fn decode_command(addr: u32, val: u32) -> DecodedCommand {
+    let command = cb1::command(addr);
+    let mut address = addr & 0x0fff_ffff;
+
+    let operation = match command {
+        0x0 => Operation::Write {
+            width: Width::Bits8,
+            value: val & 0xff,
+        },
+        0x1 => Operation::Write {
+            width: Width::Bits16,
+            value: val & 0xffff,
+        },
+        0x2 => Operation::Write {
+            width: Width::Bits32,
+            value: val,
+        },
+        0xc => Operation::Conditional {
+            width: Width::Bits32,
+            comparison: Comparison::Equal,
+            value: val,
+            lines: None,
+        },
+        0xd => conditional(val >> 24, (val >> 20) & 0xf, val, Some(1)),
+        0xe => {
+            address = val & 0x0fff_ffff;
+            conditional(addr >> 24, val >> 28, addr, Some((addr >> 16) as u8))
+        }
+        _ => Operation::Unknown,
+    };
+
+    DecodedCommand {
+        command,
+        address,
+        operation,
+    }
+}
+
+const fn conditional(width: u32, test: u32, val: u32, lines: Option<u8>) -> Operation {
+    let (width, value) = if width & 1 != 0 {
+        (Width::Bits8, val & 0xff)
+    } else {
+        (Width::Bits16, val & 0xffff)
+    };
+    match Comparison::from_bits(test) {
+        Some(comparison) => Operation::Conditional {
+            width,
+            comparison,
+            value,
+            lines,
+        },
+        None => Operation::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "std")]
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(COMMANDS.len(), 16);
+        for (i, &(cmd, desc)) in COMMANDS.iter().enumerate() {
+            assert_eq!(usize::from(cmd), i);
+            assert!(!desc.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_decode_command() {
+        let tests = &[
+            (
+                (0x0031_789a, 0x0000_0063),
+                0x0031_789a,
+                Operation::Write {
+                    width: Width::Bits8,
+                    value: 0x63,
+                },
+            ),
+            (
+                (0x1031_a028, 0x0000_ffff),
+                0x0031_a028,
+                Operation::Write {
+                    width: Width::Bits16,
+                    value: 0xffff,
+                },
+            ),
+            (
+                (0x201f_6024, 0x0000_0000),
+                0x001f_6024,
+                Operation::Write {
+                    width: Width::Bits32,
+                    value: 0,
+                },
+            ),
+            (
+                (0xc031_789a, 0x1234_5678),
+                0x0031_789a,
+                Operation::Conditional {
+                    width: Width::Bits32,
+                    comparison: Comparison::Equal,
+                    value: 0x1234_5678,
+                    lines: None,
+                },
+            ),
+            (
+                (0xd031_789a, 0x0020_ffff),
+                0x0031_789a,
+                Operation::Conditional {
+                    width: Width::Bits16,
+                    comparison: Comparison::LessThan,
+                    value: 0xffff,
+                    lines: Some(1),
+                },
+            ),
+            (
+                (0xd031_789a, 0x0170_0063),
+                0x0031_789a,
+                Operation::Conditional {
+                    width: Width::Bits8,
+                    comparison: Comparison::Or,
+                    value: 0x63,
+                    lines: Some(1),
+                },
+            ),
+            (
+                (0xe003_ffff, 0x3031_789a),
+                0x0031_789a,
+                Operation::Conditional {
+                    width: Width::Bits16,
+                    comparison: Comparison::GreaterThan,
+                    value: 0xffff,
+                    lines: Some(3),
+                },
+            ),
+            ((0xd031_789a, 0x0080_0063), 0x0031_789a, Operation::Unknown),
+            ((0x3040_0000, 0x0031_789a), 0x0040_0000, Operation::Unknown),
+            ((0x9029_beac, 0x0c0a_9225), 0x0029_beac, Operation::Unknown),
+        ];
+        for t in tests {
+            let cmd = decode_command(t.0 .0, t.0 .1);
+            assert_eq!(cmd.command, cb1::command(t.0 .0));
+            assert_eq!(cmd.address, t.1);
+            assert_eq!(cmd.operation, t.2);
+        }
+    }
+}
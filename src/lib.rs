@@ -1,7 +1,8 @@
 //! Encrypt and decrypt cheat codes for all versions of CodeBreaker PS2.
 //!
 //! Uses [cb1](cb1/index.html) and [cb7](cb7/index.html) under the hood to
-//! support both CB v1 and v7 codes.
+//! support both CB v1 and v7 codes. The [rc4](rc4/index.html) cipher used by
+//! CB v7 is available as well.
 //!
 //! # Quickstart
 //!
@@ -36,9 +37,24 @@
 #[cfg(doctest)]
 doc_comment::doctest!("../README.md", readme);
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+// Emits a debug event if the "tracing" feature is enabled.
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
+mod auto;
 pub mod cb1;
 pub mod cb7;
-mod rc4;
+mod code;
+mod command;
+pub mod prelude;
+pub mod rc4;
 
 #[cfg(test)]
 mod std_alloc {
@@ -48,24 +64,71 @@ mod std_alloc {
     #[cfg(not(feature = "std"))]
     extern crate alloc;
 
-    pub use alloc::{fmt, vec, vec::Vec};
+    #[cfg(feature = "std")]
+    pub use alloc::{collections::HashSet, format, fs};
+    pub use alloc::{string::ToString, vec, vec::Vec};
 }
 
+pub use auto::{AutoDecryptOutput, AutoDecryptState};
+pub use code::{Code, CodeParseError, MemRegion};
+#[cfg(feature = "alloc")]
+pub use code::{CodeList, Line, LineResult};
+pub use command::{decode_command, Comparison, DecodedCommand, Operation, Width, COMMANDS};
+
 use cb7::{is_beefcode, Cb7};
 
+use core::fmt;
+use core::ops::Range;
+use core::str::FromStr;
+
+#[cfg(feature = "alloc")]
+use alloc::{collections::BTreeSet, vec::Vec};
+#[cfg(feature = "alloc")]
+use bytemuck::{bytes_of, pod_read_unaligned};
+
+/// The encryption scheme of a code.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Scheme {
+pub enum Scheme {
+    /// Not encrypted.
     Raw,
+    /// Encrypted with CB v1 - v6.
     V1,
+    /// Encrypted with CB v7+.
     V7,
 }
 
+/// Parses a scheme from its name: `raw`, `v1`, or `v7`, ignoring case.
+///
+/// # Example
+/// ```
+/// use codebreaker::Scheme;
+///
+/// assert_eq!("V7".parse(), Ok(Scheme::V7));
+/// assert!("v8".parse::<Scheme>().is_err());
+/// ```
+impl FromStr for Scheme {
+    type Err = ParseSchemeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [("raw", Self::Raw), ("v1", Self::V1), ("v7", Self::V7)]
+            .into_iter()
+            .find(|(name, _)| s.eq_ignore_ascii_case(name))
+            .map(|(_, scheme)| scheme)
+            .ok_or(ParseSchemeError)
+    }
+}
+
 /// A processor for CB v1 and v7 codes.
+///
+/// Copying a processor duplicates its exact state, including a partially
+/// processed multi-line command, to continue the same list. Use
+/// [`fork_fresh`](#method.fork_fresh) to start another list with the same
+/// settings instead.
 #[derive(Debug, Clone, Copy)]
 pub struct Codebreaker {
-    scheme: Scheme,
-    cb7: Cb7,
-    code_lines: usize,
+    state: AutoDecryptState,
+    raw: bool,
+    strict: bool,
 }
 
 /// Does the same as [`new`](#method.new).
@@ -75,28 +138,227 @@ impl Default for Codebreaker {
     }
 }
 
+/// Does the same as [`from_scheme_str`](#method.from_scheme_str).
+impl TryFrom<&str> for Codebreaker {
+    type Error = ParseSchemeError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        Self::from_scheme_str(s)
+    }
+}
+
 impl Codebreaker {
     /// Returns a new processor for encrypting and decrypting a list of CB v1
     /// and v7 codes.
     pub const fn new() -> Self {
         Self {
-            scheme: Scheme::Raw,
-            cb7: Cb7::new(),
-            code_lines: 0,
+            state: AutoDecryptState::new(),
+            raw: false,
+            strict: false,
         }
     }
 
+    /// The same processor as returned by [`new_v7`](#method.new_v7), but
+    /// computed at compile time, e.g. for a `static` on embedded targets.
+    ///
+    /// A test checks that the precomputed v7 state is equal to the one that
+    /// `new_v7` derives at runtime from `BEEFC0DE 00000000`.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Codebreaker;
+    ///
+    /// static CB: Codebreaker = Codebreaker::DEFAULT_V7;
+    ///
+    /// let mut cb = CB;
+    /// assert_eq!(cb.decrypt_code(0xD08F3A49, 0x00078A53), (0x9029BEAC, 0x0C0A9225));
+    /// ```
+    pub const DEFAULT_V7: Self = Self {
+        state: AutoDecryptState::with_cb7(Scheme::V7, Cb7::default_const()),
+        raw: false,
+        strict: false,
+    };
+
     /// Returns a new processor for all CB v7 codes published on CMGSCCC.com.
     ///
     /// Lets you omit `B4336FA9 4DFEFB79` as the first code in the list.
     pub fn new_v7() -> Self {
+        Self::new_v7_with_seed(0)
+    }
+
+    /// Returns a new processor for CB v7 codes that were encrypted after
+    /// `BEEFC0DE vvvvvvvv` with the given seed value.
+    ///
+    /// Lets you omit the beefcode as the first code in the list.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Codebreaker;
+    ///
+    /// let mut cb = Codebreaker::new_v7_with_seed(0xDEADFACE);
+    /// let code = cb.decrypt_code(0xE65B5422, 0xB12543CF);
+    /// assert_eq!(code, (0x9029BEAC, 0x0C0A9225));
+    /// ```
+    pub fn new_v7_with_seed(val: u32) -> Self {
+        let cb7 = Cb7::with_beefcode(cb7::BEEFCODE, val);
         Self {
-            scheme: Scheme::V7,
-            cb7: Cb7::default(),
-            code_lines: 0,
+            state: AutoDecryptState::with_cb7(Scheme::V7, cb7),
+            raw: false,
+            strict: false,
         }
     }
 
+    /// Returns a new processor that leaves codes untouched.
+    ///
+    /// [`encrypt_code`](#method.encrypt_code) and
+    /// [`decrypt_code`](#method.decrypt_code) return their input unchanged,
+    /// but beefcodes are still tracked, so the state is the same as for any
+    /// other processor. This is useful for pipelines that want to call
+    /// `encrypt_code` regardless of whether encryption is desired.
+    /// [`auto_decrypt_code`](#method.auto_decrypt_code) is not affected.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Codebreaker;
+    ///
+    /// let mut cb = Codebreaker::new_raw();
+    /// assert_eq!(cb.encrypt_code(0x2043AFCC, 0x2411FFFF), (0x2043AFCC, 0x2411FFFF));
+    /// assert_eq!(cb.encrypt_code(0xBEEFC0DE, 0x00000000), (0xBEEFC0DE, 0x00000000));
+    /// assert_eq!(cb.encrypt_code(0x2096F5B8, 0x000000BE), (0x2096F5B8, 0x000000BE));
+    /// ```
+    pub const fn new_raw() -> Self {
+        let mut cb = Self::new();
+        cb.raw = true;
+        cb
+    }
+
+    /// Returns a new processor for the scheme named by `s`, such as from a
+    /// `--scheme` command-line flag.
+    ///
+    /// `"raw"` selects [`new_raw`](#method.new_raw), `"v1"` selects
+    /// [`new`](#method.new), and `"v7"` selects [`new_v7`](#method.new_v7).
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Codebreaker;
+    ///
+    /// let mut cb = Codebreaker::from_scheme_str("v7").unwrap();
+    /// assert_eq!(cb.decrypt_code(0xD08F3A49, 0x00078A53), (0x9029BEAC, 0x0C0A9225));
+    ///
+    /// assert!(Codebreaker::from_scheme_str("v2").is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseSchemeError`] if `s` is not a known scheme name.
+    pub fn from_scheme_str(s: &str) -> Result<Self, ParseSchemeError> {
+        Ok(match s.parse()? {
+            Scheme::Raw => Self::new_raw(),
+            Scheme::V1 => Self::new(),
+            Scheme::V7 => Self::new_v7(),
+        })
+    }
+
+    /// Returns a copy of the processor for starting a new list with the same
+    /// settings.
+    ///
+    /// The encryption scheme and v7 seeds are kept, while the state of the
+    /// current line is reset: the copy neither expects further lines of a
+    /// multi-line command nor the extra seed line of a `BEEFC0DF` code.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Codebreaker;
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// cb.auto_decrypt_code(0x4A0668F5, 0xC4370BF1); // multi-address write, two lines
+    /// assert_eq!(cb.lines_remaining(), 1);
+    /// assert_eq!(cb.fork_fresh().lines_remaining(), 0);
+    /// ```
+    pub const fn fork_fresh(&self) -> Self {
+        let mut cb = *self;
+        cb.state.code_lines = 0;
+        cb.state.started = false;
+        cb.state.cb7.begin_list();
+        cb
+    }
+
+    /// Makes [`auto_decrypt_code`](#method.auto_decrypt_code) pass through
+    /// codes with an unknown command unchanged instead of decrypting them.
+    ///
+    /// The command is the upper nibble of the code address. Known commands are
+    /// `0` - `7`, `9`, and `B` - `F`; `8` and `A` are not used by CodeBreaker.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Codebreaker;
+    ///
+    /// let mut cb = Codebreaker::new().passthrough_unknown(true);
+    /// assert_eq!(cb.auto_decrypt_code(0x9A545CC6, 0x188CBCFB), (0x9029BEAC, 0x0C0A9225));
+    /// assert_eq!(cb.auto_decrypt_code(0x80000000, 0x12345678), (0x80000000, 0x12345678));
+    /// ```
+    pub const fn passthrough_unknown(mut self, enabled: bool) -> Self {
+        self.state = self.state.passthrough_unknown(enabled);
+        self
+    }
+
+    /// Makes [`auto_decrypt_code`](#method.auto_decrypt_code) decrypt a list
+    /// as v7 if its first code looks like a CMGSCCC code without header.
+    ///
+    /// Lists of CB v7 codes published on CMGSCCC.com may or may not start with
+    /// the header `B4336FA9 4DFEFB79`. If enabled and the first code of a list
+    /// is neither the header nor looks like a raw or v1-encrypted code, but
+    /// looks like a raw code after decrypting it with the default v7 seeds, the
+    /// list is decrypted as if it started with the header.
+    ///
+    /// This is off by default, as a single line is weak evidence: some raw
+    /// codes, e.g. 8-bit writes with stray upper bits in the value, pass the
+    /// check too, and the whole list would be decrypted as v7. Only enable it
+    /// for input that is known to be v7 or raw without stray bits. Codes with
+    /// an unknown command are never taken as v7 if
+    /// [`passthrough_unknown`](#method.passthrough_unknown) is enabled.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Codebreaker;
+    ///
+    /// // Without header
+    /// let mut cb = Codebreaker::new().detect_headerless_v7(true);
+    /// assert_eq!(cb.auto_decrypt_code(0xD08F3A49, 0x00078A53), (0x9029BEAC, 0x0C0A9225));
+    ///
+    /// // With header
+    /// let mut cb = Codebreaker::new().detect_headerless_v7(true);
+    /// assert_eq!(cb.auto_decrypt_code(0xB4336FA9, 0x4DFEFB79), (0xBEEFC0DE, 0x00000000));
+    /// assert_eq!(cb.auto_decrypt_code(0xD08F3A49, 0x00078A53), (0x9029BEAC, 0x0C0A9225));
+    ///
+    /// // Disabled by default
+    /// let mut cb = Codebreaker::new();
+    /// assert_eq!(cb.auto_decrypt_code(0x00100044, 0x00000111), (0x00100044, 0x00000111));
+    /// ```
+    pub const fn detect_headerless_v7(mut self, enabled: bool) -> Self {
+        self.state = self.state.detect_headerless_v7(enabled);
+        self
+    }
+
+    /// Makes [`auto_decrypt_code_checked`](#method.auto_decrypt_code_checked)
+    /// fail on codes with an unknown command instead of guessing how to
+    /// process them.
+    ///
+    /// The command is the upper nibble of the decrypted code address.
+    /// Recognized commands are `0` - `7`, `9`, and `B` - `F`.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Codebreaker, UnknownCommand};
+    ///
+    /// let mut cb = Codebreaker::new().strict(true);
+    /// assert_eq!(cb.auto_decrypt_code_checked(0x80000000, 0x12345678), Err(UnknownCommand(0x8)));
+    /// ```
+    pub const fn strict(mut self, enabled: bool) -> Self {
+        self.strict = enabled;
+        self
+    }
+
     /// Encrypts a code and returns the result.
     ///
     /// # Example
@@ -115,6 +377,11 @@ impl Codebreaker {
 
     /// Encrypts a code directly.
     ///
+    /// An `FFFFFFFF 000xnnnn` line, which changes encryption on the device, is
+    /// encrypted like any other code, as expected by the device.
+    /// [`auto_decrypt_code`](#method.auto_decrypt_code) recognizes it after
+    /// decryption.
+    ///
     /// # Example
     /// ```
     /// use codebreaker::Codebreaker;
@@ -127,16 +394,141 @@ impl Codebreaker {
     pub fn encrypt_code_mut(&mut self, addr: &mut u32, val: &mut u32) {
         let (oldaddr, oldval) = (*addr, *val);
 
-        if self.scheme == Scheme::V7 {
-            self.cb7.encrypt_code_mut(addr, val);
+        if self.raw {
+            // leave code untouched
+        } else if self.state.scheme == Scheme::V7 {
+            self.state.cb7.encrypt_code_mut(addr, val);
         } else {
             cb1::encrypt_code_mut(addr, val);
         }
 
         if is_beefcode(oldaddr) {
-            self.cb7.beefcode(oldaddr, oldval);
-            self.scheme = Scheme::V7;
+            self.state.cb7.beefcode(oldaddr, oldval);
+            self.state.set_scheme(Scheme::V7);
+        }
+    }
+
+    /// Encrypts a code with the given scheme instead of the one from the
+    /// current state, such as when scheme boundaries are known from metadata.
+    ///
+    /// The scheme is only forced for this code; the state is not switched to
+    /// it. Beefcodes are still tracked as with
+    /// [`encrypt_code`](#method.encrypt_code): they update the v7 seeds and
+    /// switch later codes to v7. Forcing [`Scheme::V7`] uses the seeds from
+    /// the last beefcode, so one must have been processed before, unless the
+    /// processor was created with [`new_v7`](#method.new_v7).
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Codebreaker, Scheme};
+    ///
+    /// let mut cb = Codebreaker::new_v7();
+    /// assert_eq!(cb.encrypt_code_as(Scheme::V1, 0x2043AFCC, 0x2411FFFF), (0x2AFF014C, 0x2411FFFF));
+    /// assert_eq!(cb.encrypt_code_as(Scheme::V7, 0x9029BEAC, 0x0C0A9225), (0xD08F3A49, 0x00078A53));
+    /// assert_eq!(cb.encrypt_code_as(Scheme::Raw, 0x201F6024, 0x00000000), (0x201F6024, 0x00000000));
+    /// ```
+    pub fn encrypt_code_as(&mut self, scheme: Scheme, addr: u32, val: u32) -> (u32, u32) {
+        let mut code = (addr, val);
+        match scheme {
+            Scheme::Raw => {}
+            Scheme::V1 => cb1::encrypt_code_mut(&mut code.0, &mut code.1),
+            Scheme::V7 => self.state.cb7.encrypt_code_mut(&mut code.0, &mut code.1),
+        }
+
+        if is_beefcode(addr) {
+            self.state.cb7.beefcode(addr, val);
+            self.state.set_scheme(Scheme::V7);
+        }
+        code
+    }
+
+    /// Encrypts a [`Code`] and returns the result.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Code, Codebreaker};
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// assert_eq!(cb.encrypt(Code(0x2043AFCC, 0x2411FFFF)), Code(0x2AFF014C, 0x2411FFFF));
+    /// ```
+    pub fn encrypt(&mut self, code: Code) -> Code {
+        self.encrypt_code(code.0, code.1).into()
+    }
+
+    /// Like [`encrypt_code`](#method.encrypt_code), but takes and returns the
+    /// code as an array, which can be cast to bytes directly.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Codebreaker;
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// let code = cb.encrypt_code_array([0x2043AFCC, 0x2411FFFF]);
+    /// assert_eq!(code, [0x2AFF014C, 0x2411FFFF]);
+    /// assert_eq!(bytemuck::cast::<_, [u8; 8]>(code.map(u32::to_le))[..4], [0x4C, 0x01, 0xFF, 0x2A]);
+    /// ```
+    pub fn encrypt_code_array(&mut self, code: [u32; 2]) -> [u32; 2] {
+        let [mut addr, mut val] = code;
+        self.encrypt_code_mut(&mut addr, &mut val);
+        [addr, val]
+    }
+
+    /// Like [`encrypt_code`](#method.encrypt_code), but also returns how the
+    /// code affected the processor state.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Codebreaker, LineEffect};
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// assert_eq!(
+    ///     cb.encrypt_code_with_effect(0xBEEFC0DE, 0x00000000),
+    ///     ((0xB4336FA9, 0x4DFEFB79), LineEffect::EnteredV7)
+    /// );
+    /// assert_eq!(
+    ///     cb.encrypt_code_with_effect(0x2096F5B8, 0x000000BE),
+    ///     ((0x973E0B2A, 0xA7D4AF10), LineEffect::None)
+    /// );
+    /// ```
+    pub fn encrypt_code_with_effect(&mut self, addr: u32, val: u32) -> ((u32, u32), LineEffect) {
+        let was_v7 = self.state.scheme == Scheme::V7;
+        let code = self.encrypt_code(addr, val);
+        let effect = match (is_beefcode(addr), was_v7) {
+            (false, _) => LineEffect::None,
+            (true, false) => LineEffect::EnteredV7,
+            (true, true) => LineEffect::Reseeded,
+        };
+        (code, effect)
+    }
+
+    /// Like [`encrypt_code`](#method.encrypt_code), but refuses to encrypt a
+    /// code that already looks encrypted.
+    ///
+    /// Raw code addresses never have any of the bits `0x0E000000` set, while
+    /// v1-encrypted addresses usually do. Beefcodes are exempt from the check.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncryptError::LooksAlreadyEncrypted`] without changing any
+    /// state if the code looks encrypted. Use
+    /// [`encrypt_code`](#method.encrypt_code) to encrypt it anyway.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Codebreaker, EncryptError};
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// assert_eq!(cb.encrypt_code_checked(0x2043AFCC, 0x2411FFFF), Ok((0x2AFF014C, 0x2411FFFF)));
+    /// assert_eq!(
+    ///     cb.encrypt_code_checked(0x2AFF014C, 0x2411FFFF),
+    ///     Err(EncryptError::LooksAlreadyEncrypted)
+    /// );
+    /// ```
+    pub fn encrypt_code_checked(&mut self, addr: u32, val: u32) -> Result<(u32, u32), EncryptError> {
+        if looks_v1_encrypted(addr) && !is_beefcode(addr) {
+            return Err(EncryptError::LooksAlreadyEncrypted);
         }
+        Ok(self.encrypt_code(addr, val))
     }
 
     /// Decrypts a code and returns the result.
@@ -162,6 +554,19 @@ impl Codebreaker {
     ///     assert_eq!(result, decrypted[i]);
     /// }
     /// ```
+    ///
+    /// Decrypting is not idempotent: decrypting an already decrypted code
+    /// scrambles it, except with [`new_raw`](#method.new_raw). Use
+    /// [`auto_decrypt_code`](#method.auto_decrypt_code) if the input might
+    /// already be decrypted.
+    ///
+    /// ```
+    /// use codebreaker::Codebreaker;
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// assert_eq!(cb.decrypt_code(0x2AFF014C, 0x2411FFFF), (0x2043AFCC, 0x2411FFFF));
+    /// assert_eq!(cb.decrypt_code(0x2043AFCC, 0x2411FFFF), (0x2AAD2F78, 0x2411FFFF));
+    /// ```
     pub fn decrypt_code(&mut self, addr: u32, val: u32) -> (u32, u32) {
         let mut code = (addr, val);
         self.decrypt_code_mut(&mut code.0, &mut code.1);
@@ -170,6 +575,9 @@ impl Codebreaker {
 
     /// Decrypts a code directly.
     ///
+    /// Like [`encrypt_code_mut`](#method.encrypt_code_mut), an
+    /// `FFFFFFFF 000xnnnn` line is processed like any other code.
+    ///
     /// # Example
     /// ```
     /// use codebreaker::Codebreaker;
@@ -192,18 +600,109 @@ impl Codebreaker {
     /// assert_eq!(encrypted, decrypted);
     /// ```
     pub fn decrypt_code_mut(&mut self, addr: &mut u32, val: &mut u32) {
-        if self.scheme == Scheme::V7 {
-            self.cb7.decrypt_code_mut(addr, val);
+        if self.raw {
+            // leave code untouched
+        } else if self.state.scheme == Scheme::V7 {
+            self.state.cb7.decrypt_code_mut(addr, val);
         } else {
             cb1::decrypt_code_mut(addr, val);
         }
 
         if is_beefcode(*addr) {
-            self.cb7.beefcode(*addr, *val);
-            self.scheme = Scheme::V7;
+            self.state.cb7.beefcode(*addr, *val);
+            self.state.set_scheme(Scheme::V7);
         }
     }
 
+    /// Decrypts a [`Code`] and returns the result.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Code, Codebreaker};
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// assert_eq!(cb.decrypt(Code(0x2AFF014C, 0x2411FFFF)), Code(0x2043AFCC, 0x2411FFFF));
+    /// ```
+    pub fn decrypt(&mut self, code: Code) -> Code {
+        self.decrypt_code(code.0, code.1).into()
+    }
+
+    /// Like [`decrypt_code`](#method.decrypt_code), but takes and returns the
+    /// code as an array, which can be cast to bytes directly.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Codebreaker;
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// assert_eq!(cb.decrypt_code_array([0x2AFF014C, 0x2411FFFF]), [0x2043AFCC, 0x2411FFFF]);
+    /// ```
+    pub fn decrypt_code_array(&mut self, code: [u32; 2]) -> [u32; 2] {
+        let [mut addr, mut val] = code;
+        self.decrypt_code_mut(&mut addr, &mut val);
+        [addr, val]
+    }
+
+    /// Feeds the next code of a list to the processor and returns it decrypted.
+    ///
+    /// This is the same as [`decrypt_code`](#method.decrypt_code), but the name
+    /// makes clear that the processor is **stateful**: beefcodes change how all
+    /// following codes are decrypted, so codes must be fed one by one in the
+    /// order they appear in the list.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Codebreaker;
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// assert_eq!(cb.decrypt_next((0xB4336FA9, 0x4DFEFB79)), (0xBEEFC0DE, 0x00000000));
+    /// assert_eq!(cb.decrypt_next((0x973E0B2A, 0xA7D4AF10)), (0x2096F5B8, 0x000000BE));
+    /// ```
+    pub fn decrypt_next(&mut self, code: (u32, u32)) -> (u32, u32) {
+        self.decrypt_code(code.0, code.1)
+    }
+
+    /// Auto-decrypts codes from an external tokenizer, such as a parser for a
+    /// custom file format, as they are consumed.
+    ///
+    /// Each code is decrypted with
+    /// [`auto_decrypt_code`](#method.auto_decrypt_code). Tokenizer errors are
+    /// passed through without affecting the state.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Codebreaker;
+    ///
+    /// // One code per "addr:val" token
+    /// let input = "2A973DBD:00000000;B4336FA9:4DFEFB79;bogus;973E0B2A:A7D4AF10";
+    /// let tokens = input.split(';').map(|token| {
+    ///     let (addr, val) = token.split_once(':').ok_or(token)?;
+    ///     let parse = |s| u32::from_str_radix(s, 16).map_err(|_| token);
+    ///     Ok((parse(addr)?, parse(val)?))
+    /// });
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// let output: Vec<_> = cb.decrypt_tokens(tokens).collect();
+    /// assert_eq!(
+    ///     output,
+    ///     [
+    ///         Ok((0x201F6024, 0x00000000)),
+    ///         Ok((0xBEEFC0DE, 0x00000000)),
+    ///         Err("bogus"),
+    ///         Ok((0x2096F5B8, 0x000000BE)),
+    ///     ]
+    /// );
+    /// ```
+    pub fn decrypt_tokens<'a, I, E>(&'a mut self, tokens: I) -> impl Iterator<Item = Result<(u32, u32), E>> + 'a
+    where
+        I: IntoIterator<Item = Result<(u32, u32), E>>,
+        I::IntoIter: 'a,
+    {
+        tokens
+            .into_iter()
+            .map(move |token| token.map(|(addr, val)| self.auto_decrypt_code(addr, val)))
+    }
+
     /// Smart version of [`decrypt_code`](#method.decrypt_code) that detects if
     /// and how a code needs to be decrypted.
     ///
@@ -229,77 +728,1031 @@ impl Codebreaker {
     ///     assert_eq!(cb.auto_decrypt_code(code.0, code.1), output[i]);
     /// }
     /// ```
+    ///
+    /// Raw codes are passed through unchanged, so auto-decrypting the output
+    /// again with a fresh processor returns the same list, as long as no
+    /// decrypted address other than a beefcode has any of the bits `0E000000`
+    /// set, which raw addresses below `02000000` never have, and
+    /// [`detect_headerless_v7`](#method.detect_headerless_v7) is not enabled.
+    /// Raw beefcodes are passed through as well.
+    ///
+    /// ```
+    /// use codebreaker::Codebreaker;
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// assert_eq!(cb.auto_decrypt_code(0x2043AFCC, 0x2411FFFF), (0x2043AFCC, 0x2411FFFF));
+    /// assert_eq!(cb.auto_decrypt_code(0xBEEFC0DE, 0x00000000), (0xBEEFC0DE, 0x00000000));
+    /// ```
     pub fn auto_decrypt_code(&mut self, addr: u32, val: u32) -> (u32, u32) {
         let mut code = (addr, val);
         self.auto_decrypt_code_mut(&mut code.0, &mut code.1);
         code
     }
 
+    /// Smart version of [`decrypt`](#method.decrypt) that detects if and how a
+    /// [`Code`] needs to be decrypted.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Code, Codebreaker};
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// assert_eq!(cb.auto_decrypt(Code(0x2043AFCC, 0x2411FFFF)), Code(0x2043AFCC, 0x2411FFFF));
+    /// assert_eq!(cb.auto_decrypt(Code(0x2A973DBD, 0x00000000)), Code(0x201F6024, 0x00000000));
+    /// ```
+    pub fn auto_decrypt(&mut self, code: Code) -> Code {
+        self.auto_decrypt_code(code.0, code.1).into()
+    }
+
+    /// Like [`auto_decrypt_code`](#method.auto_decrypt_code), but also
+    /// reports how the code was handled.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Code, Codebreaker, LineCategory};
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// assert_eq!(
+    ///     cb.auto_decrypt_classified(0x2A973DBD, 0x00000000),
+    ///     (Code(0x201F6024, 0x00000000), LineCategory::V1)
+    /// );
+    /// assert_eq!(
+    ///     cb.auto_decrypt_classified(0xB4336FA9, 0x4DFEFB79),
+    ///     (Code(0xBEEFC0DE, 0x00000000), LineCategory::Header)
+    /// );
+    /// assert_eq!(
+    ///     cb.auto_decrypt_classified(0x973E0B2A, 0xA7D4AF10),
+    ///     (Code(0x2096F5B8, 0x000000BE), LineCategory::V7)
+    /// );
+    /// ```
+    pub fn auto_decrypt_classified(&mut self, addr: u32, val: u32) -> (Code, LineCategory) {
+        let code = Code::from(self.auto_decrypt_code(addr, val));
+        let category = if (addr, val) == V7_HEADER {
+            LineCategory::Header
+        } else if code.is_beefcode() {
+            LineCategory::Beefcode
+        } else if code.as_tuple() == (addr, val) {
+            // Raw codes and those passed through as unknown
+            LineCategory::Raw
+        } else if self.state.scheme == Scheme::V7 {
+            LineCategory::V7
+        } else {
+            LineCategory::V1
+        };
+        (code, category)
+    }
+
     /// Smart version of [`decrypt_code_mut`](#method.decrypt_code_mut) that
     /// detects if and how a code needs to be decrypted.
+    ///
+    /// A v7 list is detected by its header `B4336FA9 4DFEFB79` or any other
+    /// beefcode. See [`detect_headerless_v7`](#method.detect_headerless_v7)
+    /// for lists without header.
     pub fn auto_decrypt_code_mut(&mut self, addr: &mut u32, val: &mut u32) {
-        if self.scheme != Scheme::V7 {
-            if self.code_lines == 0 {
-                self.code_lines = num_code_lines(*addr);
-                if (*addr >> 24) & 0x0e != 0 {
-                    if is_beefcode(*addr) {
-                        // ignore raw beefcode
-                        self.code_lines -= 1;
-                        return;
-                    }
-                    self.scheme = Scheme::V1;
-                    self.code_lines -= 1;
-                    cb1::decrypt_code_mut(addr, val);
-                } else {
-                    self.scheme = Scheme::Raw;
-                    self.code_lines -= 1;
-                }
-            } else {
-                self.code_lines -= 1;
-                if self.scheme == Scheme::Raw {
-                    return;
-                }
-                cb1::decrypt_code_mut(addr, val);
-            }
-        } else {
-            self.cb7.decrypt_code_mut(addr, val);
-            if self.code_lines == 0 {
-                self.code_lines = num_code_lines(*addr);
-                if self.code_lines == 1 && *addr == 0xffff_ffff {
-                    // XXX: changing encryption via "FFFFFFFF 000xnnnn" is not supported
-                    self.code_lines = 0;
-                    return;
-                }
-            }
-            self.code_lines -= 1;
-        }
-
-        if is_beefcode(*addr) {
-            self.cb7.beefcode(*addr, *val);
-            self.scheme = Scheme::V7;
-            self.code_lines = 1;
-        }
+        self.state.step_mut(addr, val);
     }
-}
 
-const fn num_code_lines(addr: u32) -> usize {
-    let cmd = addr >> 28;
+    /// Encrypts a list of codes in order and returns the results.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Code, Codebreaker};
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// let codes = [Code(0x2043AFCC, 0x2411FFFF), Code(0xBEEFC0DE, 0x00000000), Code(0x2096F5B8, 0x000000BE)];
+    /// assert_eq!(
+    ///     cb.encrypt_codes(&codes),
+    ///     [Code(0x2AFF014C, 0x2411FFFF), Code(0xB4336FA9, 0x4DFEFB79), Code(0x973E0B2A, 0xA7D4AF10)]
+    /// );
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn encrypt_codes(&mut self, codes: &[Code]) -> Vec<Code> {
+        codes.iter().map(|&code| self.encrypt(code)).collect()
+    }
 
-    if cmd < 3 || cmd > 6 {
-        1
-    } else if cmd == 3 {
-        if addr & 0x0040_0000 != 0 {
-            2
-        } else {
-            1
-        }
-    } else {
-        2
+    /// Decrypts a list of codes in order and returns the results.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Code, Codebreaker};
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// let codes = [Code(0x2AFF014C, 0x2411FFFF), Code(0xB4336FA9, 0x4DFEFB79), Code(0x973E0B2A, 0xA7D4AF10)];
+    /// assert_eq!(
+    ///     cb.decrypt_codes(&codes),
+    ///     [Code(0x2043AFCC, 0x2411FFFF), Code(0xBEEFC0DE, 0x00000000), Code(0x2096F5B8, 0x000000BE)]
+    /// );
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn decrypt_codes(&mut self, codes: &[Code]) -> Vec<Code> {
+        codes.iter().map(|&code| self.decrypt(code)).collect()
     }
-}
 
-#[cfg(test)]
-mod tests {
+    /// Decrypts a list of labeled codes in order and returns the results with
+    /// their labels.
+    ///
+    /// Beefcodes change how all following codes are decrypted, so the order of
+    /// codes matters. Keep codes in a slice or `Vec` instead of an unordered
+    /// collection like `HashMap`, and use this method to carry any metadata
+    /// along.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Code, Codebreaker};
+    ///
+    /// let codes = [
+    ///     ("Mastercode", Code(0x9A545CC6, 0x188CBCFB)),
+    ///     ("Infinite Ammo", Code(0x2A973DBD, 0x00000000)),
+    /// ];
+    /// let mut cb = Codebreaker::new();
+    /// assert_eq!(
+    ///     cb.decrypt_ordered(&codes),
+    ///     [("Mastercode", Code(0x9029BEAC, 0x0C0A9225)), ("Infinite Ammo", Code(0x201F6024, 0x00000000))]
+    /// );
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn decrypt_ordered<K: Clone>(&mut self, codes: &[(K, Code)]) -> Vec<(K, Code)> {
+        codes
+            .iter()
+            .map(|(key, code)| (key.clone(), self.decrypt(*code)))
+            .collect()
+    }
+
+    /// Auto-decrypts a list of codes in order and returns the results.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Code, Codebreaker};
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// let codes = [Code(0x9A545CC6, 0x188CBCFB), Code(0x2A973DBD, 0x00000000)];
+    /// assert_eq!(cb.auto_decrypt_codes(&codes), [Code(0x9029BEAC, 0x0C0A9225), Code(0x201F6024, 0x00000000)]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn auto_decrypt_codes(&mut self, codes: &[Code]) -> Vec<Code> {
+        codes.iter().map(|&code| self.auto_decrypt(code)).collect()
+    }
+
+    /// Like [`auto_decrypt_code`](#method.auto_decrypt_code), but fails on
+    /// codes with an unknown command if [`strict`](#method.strict) mode is
+    /// enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`UnknownCommand`] without changing any state if strict mode is
+    /// enabled and the code starts a command that is not recognized.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Codebreaker, UnknownCommand};
+    ///
+    /// let mut cb = Codebreaker::new().strict(true);
+    /// assert_eq!(cb.auto_decrypt_code_checked(0x9A545CC6, 0x188CBCFB), Ok((0x9029BEAC, 0x0C0A9225)));
+    /// assert_eq!(cb.auto_decrypt_code_checked(0xA0000000, 0x00000000), Err(UnknownCommand(0xA)));
+    /// ```
+    pub fn auto_decrypt_code_checked(&mut self, addr: u32, val: u32) -> Result<(u32, u32), UnknownCommand> {
+        let mut state = self.state;
+        let new_command = state.code_lines == 0;
+        let mut code = (addr, val);
+        state.step_mut(&mut code.0, &mut code.1);
+
+        if self.strict && new_command && !is_known_command(code.0) {
+            return Err(UnknownCommand(cb1::command(code.0)));
+        }
+        self.state = state;
+        Ok(code)
+    }
+
+    /// Auto-decrypts a list of codes and reports for each code whether it was
+    /// actually transformed (`true`) or passed through unchanged (`false`).
+    ///
+    /// This helps to detect lists that were expected to be encrypted but are
+    /// raw.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Code, Codebreaker};
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// let report = cb.decrypt_report(&[(0x2043AFCC, 0x2411FFFF), (0x2A973DBD, 0x00000000)]);
+    /// assert_eq!(report, [(Code(0x2043AFCC, 0x2411FFFF), false), (Code(0x201F6024, 0x00000000), true)]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn decrypt_report(&mut self, codes: &[(u32, u32)]) -> Vec<(Code, bool)> {
+        codes
+            .iter()
+            .map(|&(addr, val)| {
+                let code = self.auto_decrypt_code(addr, val);
+                (code.into(), code != (addr, val))
+            })
+            .collect()
+    }
+
+    /// Converts a list of codes to the given scheme in place, e.g. to turn a
+    /// v1 list into a v7 list.
+    ///
+    /// The list is auto-decrypted with a fresh processor and then encrypted
+    /// like [`encrypt_list`]. For [`Scheme::V7`], the v7 header is prepended
+    /// unless the list starts with a beefcode. For the other schemes, all
+    /// beefcodes are removed, including the extra seed line of `BEEFC0DF`, as
+    /// they only apply to v7.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Code, Codebreaker, Scheme};
+    ///
+    /// let mut codes = vec![Code(0x2A973DBD, 0x00000000), Code(0x2A03B60A, 0x000000BE)];
+    /// Codebreaker::reencrypt_in_place(&mut codes, Scheme::V7);
+    /// assert_eq!(
+    ///     codes,
+    ///     [Code(0xB4336FA9, 0x4DFEFB79), Code(0x3818DDE5, 0xE72B2B16), Code(0x973E0B2A, 0xA7D4AF10)]
+    /// );
+    ///
+    /// Codebreaker::reencrypt_in_place(&mut codes, Scheme::V1);
+    /// assert_eq!(codes, [Code(0x2A973DBD, 0x00000000), Code(0x2A03B60A, 0x000000BE)]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn reencrypt_in_place(codes: &mut Vec<Code>, to: Scheme) {
+        let mut cb = Self::new();
+        let mut decrypted: Vec<(u32, u32)> = codes.iter().map(|code| cb.auto_decrypt_code(code.0, code.1)).collect();
+        if to != Scheme::V7 {
+            let mut seed_line = false;
+            decrypted.retain(|&(addr, _)| {
+                let keep = !seed_line && !is_beefcode(addr);
+                seed_line = is_beefcode(addr) && addr & 1 != 0;
+                keep
+            });
+        }
+        *codes = encrypt_list_detailed(&decrypted, to).lines;
+    }
+
+    /// Auto-decrypts a list of codes and removes duplicates from the result,
+    /// keeping the first occurrence of each code.
+    ///
+    /// Duplicates are detected after decryption, as the same code can be
+    /// encrypted differently, such as when merging v1 and v7 lists.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Code, Codebreaker};
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// let codes = cb.decrypt_dedup(&[(0x2043AFCC, 0x2411FFFF), (0x2AFF014C, 0x2411FFFF)]);
+    /// assert_eq!(codes, [Code(0x2043AFCC, 0x2411FFFF)]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn decrypt_dedup(&mut self, codes: &[(u32, u32)]) -> Vec<Code> {
+        let mut seen = BTreeSet::new();
+        codes
+            .iter()
+            .map(|&(addr, val)| Code::from(self.auto_decrypt_code(addr, val)))
+            .filter(|&code| seen.insert(code))
+            .collect()
+    }
+
+    /// Auto-decrypts the codes in a list of lines, keeping comments in place.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Code, Codebreaker, Line};
+    ///
+    /// let input = vec![
+    ///     Line::Comment("Mastercode".to_string()),
+    ///     Line::Code(Code(0x9A545CC6, 0x188CBCFB)),
+    ///     Line::Comment("Infinite Ammo".to_string()),
+    ///     Line::Code(Code(0x2A973DBD, 0x00000000)),
+    /// ];
+    /// let output = vec![
+    ///     Line::Comment("Mastercode".to_string()),
+    ///     Line::Code(Code(0x9029BEAC, 0x0C0A9225)),
+    ///     Line::Comment("Infinite Ammo".to_string()),
+    ///     Line::Code(Code(0x201F6024, 0x00000000)),
+    /// ];
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// assert_eq!(cb.decrypt_annotated(&input), output);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn decrypt_annotated(&mut self, lines: &[Line]) -> Vec<Line> {
+        lines
+            .iter()
+            .map(|line| match line {
+                Line::Code(code) => Line::Code(self.auto_decrypt_code(code.0, code.1).into()),
+                Line::Comment(_) => line.clone(),
+            })
+            .collect()
+    }
+
+    /// Parses a line of text and auto-decrypts it if it is a code.
+    ///
+    /// A line is considered a code if it consists of exactly two words, each
+    /// made of 8 hex digits or starting with `0x`. Any other non-blank line,
+    /// like `Dead Face`, is a comment and does not affect the state of the
+    /// processor.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Code, CodeParseError, Codebreaker, LineResult};
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// assert_eq!(cb.process_line("Infinite Ammo"), LineResult::Comment("Infinite Ammo".to_string()));
+    /// assert_eq!(cb.process_line("2A973DBD 00000000"), LineResult::Code(Code(0x201F6024, 0x00000000)));
+    /// assert_eq!(cb.process_line(""), LineResult::Blank);
+    /// assert_eq!(cb.process_line("0x2A973DBD 0x2A973DBD0"), LineResult::ParseError(CodeParseError::InvalidWord));
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn process_line(&mut self, line: &str) -> LineResult {
+        let line = line.trim();
+        if line.is_empty() {
+            return LineResult::Blank;
+        }
+        match Code::parse_list_line(line) {
+            Some(Ok(code)) => LineResult::Code(self.auto_decrypt_code(code.0, code.1).into()),
+            Some(Err(err)) => LineResult::ParseError(err),
+            None => LineResult::Comment(line.into()),
+        }
+    }
+
+    /// Returns the number of lines that
+    /// [`auto_decrypt_code`](#method.auto_decrypt_code) still expects for the
+    /// current multi-line command.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Codebreaker;
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// cb.auto_decrypt_code(0x30400000, 0x0043AFCC); // 32-bit increment
+    /// assert_eq!(cb.lines_remaining(), 1);
+    /// cb.auto_decrypt_code(0x00000001, 0x00000000);
+    /// assert_eq!(cb.lines_remaining(), 0);
+    /// ```
+    pub const fn lines_remaining(&self) -> usize {
+        self.state.code_lines
+    }
+
+    /// Checks that [`auto_decrypt_code`](#method.auto_decrypt_code) is not in
+    /// the middle of a command at the end of a list, which catches truncated
+    /// input.
+    ///
+    /// The pending state is cleared in any case, so the processor can be used
+    /// for another list.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FinalizeError::DanglingBeefcodf`] if the list ended with a
+    /// `BEEFC0DF` code whose extra seed line is missing, or
+    /// [`FinalizeError::IncompleteCommand`] if lines of a multi-line command
+    /// are missing. The latter includes a list that ends with a beefcode.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Codebreaker, FinalizeError};
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// cb.auto_decrypt_code(0x30400000, 0x0043AFCC); // 32-bit increment
+    /// assert_eq!(cb.finish(), Err(FinalizeError::IncompleteCommand(1)));
+    /// assert_eq!(cb.finish(), Ok(()));
+    /// ```
+    pub const fn finish(&mut self) -> Result<(), FinalizeError> {
+        let code_lines = self.state.code_lines;
+        self.state.code_lines = 0;
+        if self.state.cb7.finish().is_err() {
+            return Err(FinalizeError::DanglingBeefcodf);
+        }
+        if code_lines != 0 {
+            return Err(FinalizeError::IncompleteCommand(code_lines));
+        }
+        Ok(())
+    }
+
+    /// Returns the scheme [`auto_decrypt_code`](#method.auto_decrypt_code)
+    /// would use for a code with the given address, without changing any
+    /// state.
+    ///
+    /// Detecting a list of v7 codes without header, if enabled with
+    /// [`detect_headerless_v7`](#method.detect_headerless_v7), also requires
+    /// the code value and is not taken into account.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::{Codebreaker, Scheme};
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// assert_eq!(cb.peek_scheme(0x2043AFCC), Scheme::Raw);
+    /// assert_eq!(cb.peek_scheme(0x2A973DBD), Scheme::V1);
+    ///
+    /// cb.auto_decrypt_code(0xB4336FA9, 0x4DFEFB79);
+    /// assert_eq!(cb.peek_scheme(0x2043AFCC), Scheme::V7);
+    /// ```
+    pub const fn peek_scheme(&self, addr: u32) -> Scheme {
+        if matches!(self.state.scheme, Scheme::V7) || self.state.code_lines != 0 {
+            return self.state.scheme;
+        }
+        if (self.state.passthrough_unknown && !is_known_command(addr)) || is_beefcode(addr) {
+            return self.state.scheme;
+        }
+        if looks_v1_encrypted(addr) {
+            Scheme::V1
+        } else {
+            Scheme::Raw
+        }
+    }
+
+    /// Auto-decrypts a buffer of codes stored as little-endian `u32` pairs and
+    /// returns the decrypted codes in the same layout.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LenError`] if the buffer length is not a multiple of 8.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Codebreaker;
+    ///
+    /// let input = [0xBD, 0x3D, 0x97, 0x2A, 0x00, 0x00, 0x00, 0x00];
+    /// let mut cb = Codebreaker::new();
+    /// let output = cb.decrypt_bytes(&input).unwrap();
+    /// assert_eq!(output, [0x24, 0x60, 0x1F, 0x20, 0x00, 0x00, 0x00, 0x00]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn decrypt_bytes(&mut self, input: &[u8]) -> Result<Vec<u8>, LenError> {
+        let chunks = input.chunks_exact(8);
+        if !chunks.remainder().is_empty() {
+            return Err(LenError(input.len()));
+        }
+
+        let mut output = Vec::with_capacity(input.len());
+        for chunk in chunks {
+            let [mut addr, mut val] = pod_read_unaligned::<[u32; 2]>(chunk).map(u32::from_le);
+            self.auto_decrypt_code_mut(&mut addr, &mut val);
+            output.extend_from_slice(bytes_of(&[addr.to_le(), val.to_le()]));
+        }
+        Ok(output)
+    }
+
+    /// Auto-decrypts a bounded list of codes in place, without requiring an
+    /// allocator.
+    ///
+    /// Requires the "heapless" feature.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::Codebreaker;
+    ///
+    /// let mut codes: heapless::Vec<(u32, u32), 4> = heapless::Vec::new();
+    /// codes.push((0x2A973DBD, 0x00000000)).unwrap();
+    /// codes.push((0x2A03B60A, 0x000000BE)).unwrap();
+    ///
+    /// let mut cb = Codebreaker::new();
+    /// cb.decrypt_heapless(&mut codes);
+    /// assert_eq!(codes, [(0x201F6024, 0x00000000), (0x2096F5B8, 0x000000BE)]);
+    /// ```
+    #[cfg(feature = "heapless")]
+    pub fn decrypt_heapless<const N: usize>(&mut self, codes: &mut heapless::Vec<(u32, u32), N>) {
+        for (addr, val) in codes.iter_mut() {
+            self.auto_decrypt_code_mut(addr, val);
+        }
+    }
+}
+
+/// Error returned when a byte buffer does not hold a whole number of codes.
+///
+/// Contains the length of the buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LenError(pub usize);
+
+impl fmt::Display for LenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "buffer length {} is not a multiple of 8", self.0)
+    }
+}
+
+impl core::error::Error for LenError {}
+
+/// Error returned by [`Codebreaker::auto_decrypt_code_checked`] in strict mode
+/// if a code has an unknown command.
+///
+/// Contains the command nibble.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownCommand(pub u8);
+
+impl fmt::Display for UnknownCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown command {:X}", self.0)
+    }
+}
+
+impl core::error::Error for UnknownCommand {}
+
+/// Error returned by [`Codebreaker::encrypt_code_checked`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncryptError {
+    /// The code looks like it is already encrypted.
+    LooksAlreadyEncrypted,
+}
+
+impl fmt::Display for EncryptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LooksAlreadyEncrypted => f.write_str("code looks already encrypted"),
+        }
+    }
+}
+
+impl core::error::Error for EncryptError {}
+
+/// Error returned when parsing a [`Scheme`] from a string fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseSchemeError;
+
+impl fmt::Display for ParseSchemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("expected scheme raw, v1, or v7")
+    }
+}
+
+impl core::error::Error for ParseSchemeError {}
+
+/// Error returned by [`Codebreaker::finish`] if a list is incomplete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinalizeError {
+    /// Lines of a multi-line command are missing; holds their number.
+    IncompleteCommand(usize),
+    /// The extra seed line of a `BEEFC0DF` code is missing.
+    DanglingBeefcodf,
+}
+
+impl fmt::Display for FinalizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IncompleteCommand(n) => write!(f, "list ended with {n} line(s) of a command missing"),
+            Self::DanglingBeefcodf => f.write_str("BEEFC0DF code is missing its extra seed line"),
+        }
+    }
+}
+
+impl core::error::Error for FinalizeError {}
+
+/// The effect a code had on the state of a [`Codebreaker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEffect {
+    /// The state is unchanged.
+    None,
+    /// The code was a beefcode that switched to v7 encryption.
+    EnteredV7,
+    /// The code was a beefcode that changed the v7 seeds.
+    Reseeded,
+}
+
+/// How a code was handled by [`Codebreaker::auto_decrypt_classified`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineCategory {
+    /// The code was not encrypted and is returned as is.
+    Raw,
+    /// The code was decrypted with CB v1 encryption.
+    V1,
+    /// The code was decrypted with CB v7 encryption.
+    V7,
+    /// The code is a beefcode, other than the standard v7 header.
+    Beefcode,
+    /// The code is the standard v7 header `B4336FA9 4DFEFB79`.
+    Header,
+}
+
+/// Auto-decrypts a list of codes with a fresh processor and pairs each input
+/// code with its result.
+///
+/// Since decryption is stateful, the result is not the same as decrypting each
+/// code on its own.
+///
+/// # Example
+/// ```
+/// use codebreaker::{zip_transform, Code};
+///
+/// let input: Vec<(u32, u32)> = vec![
+///     (0x2043AFCC, 0x2411FFFF),
+///     (0xB4336FA9, 0x4DFEFB79),
+///     (0x973E0B2A, 0xA7D4AF10),
+/// ];
+/// let pairs = zip_transform(&input);
+/// assert_eq!(pairs[1], (Code(0xB4336FA9, 0x4DFEFB79), Code(0xBEEFC0DE, 0x00000000)));
+/// assert_eq!(pairs[2], (Code(0x973E0B2A, 0xA7D4AF10), Code(0x2096F5B8, 0x000000BE)));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn zip_transform(input: &[(u32, u32)]) -> Vec<(Code, Code)> {
+    let mut cb = Codebreaker::new();
+    input
+        .iter()
+        .map(|&(addr, val)| (Code(addr, val), cb.auto_decrypt_code(addr, val).into()))
+        .collect()
+}
+
+/// The number of codes after which [`decrypt_list_with_progress`] reports
+/// progress.
+pub const PROGRESS_INTERVAL: usize = 1024;
+
+/// Auto-decrypts a list of codes with a fresh processor, like
+/// [`Codebreaker::auto_decrypt_code`], and reports progress for huge lists.
+///
+/// `progress` is called with the number of codes processed so far after every
+/// [`PROGRESS_INTERVAL`] codes, and after the last code.
+///
+/// # Example
+/// ```
+/// use codebreaker::{decrypt_list_with_progress, PROGRESS_INTERVAL};
+///
+/// let codes = vec![(0x2A973DBD, 0x00000000); PROGRESS_INTERVAL + 1];
+/// let mut reports = Vec::new();
+/// let output = decrypt_list_with_progress(&codes, |n| reports.push(n));
+/// assert_eq!(output[0], (0x201F6024, 0x00000000));
+/// assert_eq!(reports, [PROGRESS_INTERVAL, PROGRESS_INTERVAL + 1]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn decrypt_list_with_progress(codes: &[(u32, u32)], mut progress: impl FnMut(usize)) -> Vec<(u32, u32)> {
+    let mut cb = Codebreaker::new();
+    let mut output = Vec::with_capacity(codes.len());
+    for (chunk, n) in codes.chunks(PROGRESS_INTERVAL).zip(0..) {
+        output.extend(chunk.iter().map(|&(addr, val)| cb.auto_decrypt_code(addr, val)));
+        progress(n * PROGRESS_INTERVAL + chunk.len());
+    }
+    output
+}
+
+/// Auto-decrypts a list of codes with a fresh processor and splits the result
+/// into runs of codes that were encrypted with the same scheme.
+///
+/// A beefcode starts a [`Scheme::V7`] run, so that each v7 run can be
+/// re-encrypted with its header.
+///
+/// # Example
+/// ```
+/// use codebreaker::{split_by_scheme, Code, Scheme};
+///
+/// let input = [
+///     (0x2043AFCC, 0x2411FFFF),
+///     (0x2A973DBD, 0x00000000),
+///     (0xB4336FA9, 0x4DFEFB79),
+///     (0x973E0B2A, 0xA7D4AF10),
+/// ];
+/// let blocks = split_by_scheme(&input);
+/// assert_eq!(blocks[0], (Scheme::Raw, vec![Code(0x2043AFCC, 0x2411FFFF)]));
+/// assert_eq!(blocks[1], (Scheme::V1, vec![Code(0x201F6024, 0x00000000)]));
+/// assert_eq!(
+///     blocks[2],
+///     (Scheme::V7, vec![Code(0xBEEFC0DE, 0x00000000), Code(0x2096F5B8, 0x000000BE)])
+/// );
+/// ```
+#[cfg(feature = "alloc")]
+pub fn split_by_scheme(input: &[(u32, u32)]) -> Vec<(Scheme, Vec<Code>)> {
+    let mut state = AutoDecryptState::new();
+    let mut blocks: Vec<(Scheme, Vec<Code>)> = Vec::new();
+    for &code in input {
+        let out = state.step(code.into());
+        match blocks.last_mut() {
+            Some((scheme, codes)) if *scheme == out.scheme => codes.push(out.code),
+            _ => blocks.push((out.scheme, alloc::vec![out.code])),
+        }
+    }
+    blocks
+}
+
+/// Encrypts a list of raw codes for the given scheme with a fresh processor.
+///
+/// For [`Scheme::V7`], the v7 header `B4336FA9 4DFEFB79` is prepended unless
+/// the list already starts with a beefcode. [`Scheme::Raw`] returns the codes
+/// unchanged.
+///
+/// # Example
+/// ```
+/// use codebreaker::{encrypt_list, Scheme};
+///
+/// let codes = [(0x2096F5B8, 0x000000BE)];
+/// assert_eq!(encrypt_list(&codes, Scheme::V1), [(0x2A03B60A, 0x000000BE)]);
+/// assert_eq!(
+///     encrypt_list(&codes, Scheme::V7),
+///     [(0xB4336FA9, 0x4DFEFB79), (0x973E0B2A, 0xA7D4AF10)]
+/// );
+/// ```
+#[cfg(feature = "alloc")]
+pub fn encrypt_list(codes: &[(u32, u32)], scheme: Scheme) -> Vec<(u32, u32)> {
+    let result = encrypt_list_detailed(codes, scheme);
+    result.lines.iter().map(Code::as_tuple).collect()
+}
+
+/// Like [`encrypt_list`], but also reports whether the v7 header was
+/// prepended.
+///
+/// # Example
+/// ```
+/// use codebreaker::{encrypt_list_detailed, Code, Scheme};
+///
+/// let result = encrypt_list_detailed(&[(0x2096F5B8, 0x000000BE)], Scheme::V7);
+/// assert_eq!(result.lines, [Code(0xB4336FA9, 0x4DFEFB79), Code(0x973E0B2A, 0xA7D4AF10)]);
+/// assert!(result.header_inserted);
+/// assert_eq!(result.code_lines(), 1);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn encrypt_list_detailed(codes: &[(u32, u32)], scheme: Scheme) -> EncryptResult {
+    let mut lines = Vec::with_capacity(codes.len() + 1);
+    let mut header_inserted = false;
+    let mut cb = match scheme {
+        Scheme::Raw => Codebreaker::new_raw(),
+        Scheme::V1 => Codebreaker::new(),
+        Scheme::V7 if codes.first().is_some_and(|code| is_beefcode(code.0)) => Codebreaker::new(),
+        Scheme::V7 => {
+            lines.push(V7_HEADER.into());
+            header_inserted = true;
+            Codebreaker::new_v7()
+        }
+    };
+    lines.extend(codes.iter().map(|&(addr, val)| Code::from(cb.encrypt_code(addr, val))));
+    EncryptResult { lines, header_inserted }
+}
+
+/// The result of [`encrypt_list_detailed`].
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptResult {
+    /// The encrypted codes, including the v7 header if it was prepended.
+    pub lines: Vec<Code>,
+    /// Whether the v7 header `B4336FA9 4DFEFB79` was prepended.
+    pub header_inserted: bool,
+}
+
+#[cfg(feature = "alloc")]
+impl EncryptResult {
+    /// Returns the number of encrypted codes, not counting a prepended
+    /// header, which matches the number of input codes.
+    pub fn code_lines(&self) -> usize {
+        self.lines.len() - usize::from(self.header_inserted)
+    }
+}
+
+/// Encrypts the raw codes in `range` of an otherwise encrypted list in place,
+/// leaving the other codes untouched, such as when regenerating part of a
+/// file.
+///
+/// The processor state is not stored in the list, so it is replayed from the
+/// start up to `range.start`: those codes are auto-decrypted with a fresh
+/// processor and the results discarded. This picks up the v7 header and any
+/// other beefcode before the range, so that codes in it are encrypted with v7
+/// and the right seeds.
+///
+/// # Panics
+///
+/// Panics if `range` is out of bounds for `codes`.
+///
+/// # Example
+/// ```
+/// use codebreaker::encrypt_range;
+///
+/// let mut codes = [
+///     (0xB4336FA9, 0x4DFEFB79),
+///     (0x2096F5B8, 0x000000BE),
+/// ];
+/// encrypt_range(&mut codes, 1..2);
+/// assert_eq!(codes, [(0xB4336FA9, 0x4DFEFB79), (0x973E0B2A, 0xA7D4AF10)]);
+/// ```
+pub fn encrypt_range(codes: &mut [(u32, u32)], range: Range<usize>) {
+    let mut cb = Codebreaker::new();
+    for &(addr, val) in &codes[..range.start] {
+        cb.auto_decrypt_code(addr, val);
+    }
+    for (addr, val) in &mut codes[range] {
+        cb.encrypt_code_mut(addr, val);
+    }
+}
+
+/// Guesses the scheme of a whole list of codes, such as for a "guess format"
+/// button.
+///
+/// Unlike the per-line detection of [`Codebreaker::auto_decrypt_code`] and
+/// [`Codebreaker::peek_scheme`], this looks at all codes at once:
+///
+/// - A list with a v1-encrypted beefcode like the v7 header
+///   `B4336FA9 4DFEFB79` is considered [`Scheme::V7`]. A raw beefcode is not,
+///   as it is found in lists that are already decrypted.
+/// - Otherwise, the codes that look like valid commands as they are, after v1
+///   decryption, and (only) after v7 decryption with the default seeds are
+///   counted. The list is [`Scheme::V7`] if the last count is the highest,
+///   [`Scheme::V1`] if the second one is higher than the first, and
+///   [`Scheme::Raw`] if not. A single code is weak evidence, so the guess is
+///   more reliable for longer lists.
+///
+/// # Example
+/// ```
+/// use codebreaker::{detect_scheme, Scheme};
+///
+/// assert_eq!(detect_scheme(&[(0x2043AFCC, 0x2411FFFF)]), Scheme::Raw);
+/// assert_eq!(detect_scheme(&[(0x2AFF014C, 0x2411FFFF)]), Scheme::V1);
+/// assert_eq!(
+///     detect_scheme(&[(0xB4336FA9, 0x4DFEFB79), (0x973E0B2A, 0xA7D4AF10)]),
+///     Scheme::V7
+/// );
+/// ```
+pub fn detect_scheme(codes: &[(u32, u32)]) -> Scheme {
+    let has_header = codes
+        .iter()
+        .any(|&(addr, val)| !is_beefcode(addr) && is_beefcode(cb1::decrypt_code(addr, val).0));
+    if has_header {
+        return Scheme::V7;
+    }
+
+    let raw = codes.iter().filter(|&&(addr, val)| looks_raw(addr, val)).count();
+    let v1 = codes
+        .iter()
+        .filter(|&&(addr, val)| {
+            let (a, v) = cb1::decrypt_code(addr, val);
+            looks_v1_encrypted(addr) && !is_beefcode(addr) && looks_raw(a, v)
+        })
+        .count();
+    let v7 = codes.iter().filter(|&&(addr, val)| is_headerless_v7(addr, val)).count();
+    if v7 > raw && v7 > v1 {
+        Scheme::V7
+    } else if v1 > raw {
+        Scheme::V1
+    } else {
+        Scheme::Raw
+    }
+}
+
+/// The kind of a single code line as reported by [`classify_line`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    /// The v1-encrypted header `B4336FA9 4DFEFB79` that starts a v7 list.
+    V7Header,
+    /// A "beefcode" that (re)seeds v7 encryption.
+    Beefcode,
+    /// An `FFFFFFFF 000xnnnn` line that changes encryption.
+    EncryptionChange,
+    /// Command `0`: 8-bit constant write.
+    Write8,
+    /// Command `1`: 16-bit constant write.
+    Write16,
+    /// Command `2`: 32-bit constant write.
+    Write32,
+    /// Command `3`: increment or decrement.
+    IncDec,
+    /// Command `4`: 32-bit multi-address write.
+    MultiWrite,
+    /// Command `5`: copy bytes.
+    Copy,
+    /// Command `6`: pointer write.
+    PointerWrite,
+    /// Command `7`: bitwise operation.
+    Bitwise,
+    /// Commands `9` and `F`: master code (hook).
+    Hook,
+    /// Command `B`: code delay.
+    Delay,
+    /// Commands `C` - `E`: conditional execution of other codes.
+    Conditional,
+    /// Commands `8` and `A`, which are not used by CodeBreaker.
+    Unknown,
+}
+
+/// Classifies a code line by its command and well-known magic values.
+///
+/// Apart from the v7 header, the code is expected to be decrypted.
+///
+/// # Example
+/// ```
+/// use codebreaker::{classify_line, LineKind};
+///
+/// assert_eq!(classify_line(0xB4336FA9, 0x4DFEFB79), LineKind::V7Header);
+/// assert_eq!(classify_line(0xBEEFC0DE, 0x00000000), LineKind::Beefcode);
+/// assert_eq!(classify_line(0xBEEFC0DF, 0xB16B00B5), LineKind::Beefcode);
+/// assert_eq!(classify_line(0xFFFFFFFF, 0x000F0001), LineKind::EncryptionChange);
+/// assert_eq!(classify_line(0x0031789A, 0x00000063), LineKind::Write8);
+/// assert_eq!(classify_line(0x1031A028, 0x0000FFFF), LineKind::Write16);
+/// assert_eq!(classify_line(0x201F6024, 0x00000000), LineKind::Write32);
+/// assert_eq!(classify_line(0x30400000, 0x0031789A), LineKind::IncDec);
+/// assert_eq!(classify_line(0x4031789A, 0x00040001), LineKind::MultiWrite);
+/// assert_eq!(classify_line(0x5031789A, 0x00000010), LineKind::Copy);
+/// assert_eq!(classify_line(0x6031789A, 0x00000063), LineKind::PointerWrite);
+/// assert_eq!(classify_line(0x7031789A, 0x00100001), LineKind::Bitwise);
+/// assert_eq!(classify_line(0x9029BEAC, 0x0C0A9225), LineKind::Hook);
+/// assert_eq!(classify_line(0xF0100000, 0x0000000E), LineKind::Hook);
+/// assert_eq!(classify_line(0xB0000000, 0x00000010), LineKind::Delay);
+/// assert_eq!(classify_line(0xD031789A, 0x00000063), LineKind::Conditional);
+/// assert_eq!(classify_line(0x8031789A, 0x00000063), LineKind::Unknown);
+/// ```
+pub const fn classify_line(addr: u32, val: u32) -> LineKind {
+    if addr == V7_HEADER.0 && val == V7_HEADER.1 {
+        return LineKind::V7Header;
+    }
+    if is_beefcode(addr) {
+        return LineKind::Beefcode;
+    }
+    if addr == 0xffff_ffff {
+        return LineKind::EncryptionChange;
+    }
+
+    match cb1::command(addr) {
+        0x0 => LineKind::Write8,
+        0x1 => LineKind::Write16,
+        0x2 => LineKind::Write32,
+        0x3 => LineKind::IncDec,
+        0x4 => LineKind::MultiWrite,
+        0x5 => LineKind::Copy,
+        0x6 => LineKind::PointerWrite,
+        0x7 => LineKind::Bitwise,
+        0x9 | 0xf => LineKind::Hook,
+        0xb => LineKind::Delay,
+        0xc..=0xe => LineKind::Conditional,
+        _ => LineKind::Unknown,
+    }
+}
+
+/// Returns true if the code is a filler line that
+/// [`Codebreaker::auto_decrypt_code`] passes through unchanged in raw and v1
+/// lists.
+///
+/// Only `00000000 00000000` qualifies. It is a valid code that writes 0 to
+/// address 0, but some lists use it as a filler or separator. As a raw code,
+/// it would switch the detected scheme of a v1 list to raw until the next
+/// code, so auto-decryption passes it through without changing the scheme or
+/// counting it as a command. Within a multi-line command, it is treated as a
+/// regular code line. In a v7 list, the line is decrypted like any other,
+/// since it may be the ciphertext of a real code, and every line needs to be
+/// decrypted to keep the processor state in sync.
+///
+/// # Example
+/// ```
+/// use codebreaker::{is_noop_line, Codebreaker};
+///
+/// assert!(is_noop_line(0x00000000, 0x00000000));
+/// assert!(!is_noop_line(0x00000000, 0x00000001));
+///
+/// let mut cb = Codebreaker::new();
+/// assert_eq!(cb.auto_decrypt_code(0x9A545CC6, 0x188CBCFB), (0x9029BEAC, 0x0C0A9225));
+/// assert_eq!(cb.auto_decrypt_code(0x00000000, 0x00000000), (0x00000000, 0x00000000));
+/// assert_eq!(cb.auto_decrypt_code(0x2A973DBD, 0x00000000), (0x201F6024, 0x00000000));
+/// ```
+pub const fn is_noop_line(addr: u32, val: u32) -> bool {
+    addr == 0 && val == 0
+}
+
+// BEEFC0DE 00000000 encrypted with CB v1
+const V7_HEADER: (u32, u32) = (0xb433_6fa9, 0x4dfe_fb79);
+
+// Checks if a code could be the first one of a CMGSCCC list without header
+fn is_headerless_v7(addr: u32, val: u32) -> bool {
+    if looks_raw(addr, val) || is_beefcode(addr) {
+        return false;
+    }
+    let (a, v) = cb1::decrypt_code(addr, val);
+    if looks_raw(a, v) || is_beefcode(a) {
+        return false;
+    }
+    let (a, v) = Cb7::default().decrypt_code(addr, val);
+    looks_raw(a, v)
+}
+
+// Checks if a code is a plausible raw code, using the fixed bits of some
+// command formats
+const fn looks_raw(addr: u32, val: u32) -> bool {
+    if looks_v1_encrypted(addr) || !is_known_command(addr) {
+        return false;
+    }
+    match cb1::command(addr) {
+        0x0 => val >> 8 == 0,
+        0x1 => val >> 16 == 0,
+        0xd if val & 0x0100_0000 != 0 => val & 0xfe8f_ff00 == 0,
+        0xd => val & 0xfe8f_0000 == 0,
+        _ => true,
+    }
+}
+
+// Raw addresses are below 0x02000000, so any of these bits hints at v1
+// encryption, which scrambles the second nibble.
+const fn looks_v1_encrypted(addr: u32) -> bool {
+    (addr >> 24) & 0x0e != 0
+}
+
+const fn is_known_command(addr: u32) -> bool {
+    !matches!(cb1::command(addr), 0x8 | 0xa)
+}
+
+const fn num_code_lines(addr: u32) -> usize {
+    let cmd = cb1::command(addr);
+
+    if cmd < 3 || cmd > 6 {
+        1
+    } else if cmd == 3 {
+        if addr & 0x0040_0000 != 0 {
+            2
+        } else {
+            1
+        }
+    } else {
+        2
+    }
+}
+
+#[cfg(test)]
+mod tests {
     use super::*;
     use crate::code::Code;
     use crate::std_alloc::{vec, Vec};
@@ -327,6 +1780,23 @@ mod tests {
                     "973E0B2A A7D4AF10".into(),
                 ],
             },
+            Test {
+                cb: Codebreaker::new(),
+                decrypted: vec![
+                    "BEEFC0DF B16B00B5".into(),
+                    "01234567 89ABCDEF".into(),
+                    "9029BEAC 0C0A9225".into(),
+                    "201F6024 00000000".into(),
+                    "2096F5B8 000000BE".into(),
+                ],
+                encrypted: vec![
+                    "B4326FA9 1F0AFA2C".into(),
+                    "862316AB C59C5FB1".into(),
+                    "06133B66 95444FF1".into(),
+                    "565FD08D 9154AFF4".into(),
+                    "4EF412FE D03E4E13".into(),
+                ],
+            },
             Test {
                 cb: Codebreaker::new_v7(),
                 decrypted: vec![
@@ -340,6 +1810,19 @@ mod tests {
                     "973E0B2A A7D4AF10".into(),
                 ],
             },
+            Test {
+                cb: Codebreaker::new_v7_with_seed(0xdead_face),
+                decrypted: vec![
+                    "9029BEAC 0C0A9225".into(),
+                    "201F6024 00000000".into(),
+                    "2096F5B8 000000BE".into(),
+                ],
+                encrypted: vec![
+                    "E65B5422 B12543CF".into(),
+                    "D14F5E52 FE26C9ED".into(),
+                    "DD9BB6F0 F5DF87F7".into(),
+                ],
+            },
             Test {
                 cb: Codebreaker::default(),
                 decrypted: vec![
@@ -356,6 +1839,46 @@ mod tests {
         ]
     }
 
+    // Loads a JSON fixture from tests/fixtures of the form:
+    // {"scheme": "raw" | "v1" | "v7", "encrypted": ["..."], "decrypted": ["..."]}
+    #[cfg(feature = "std")]
+    fn load_fixture(name: &str) -> Test {
+        use crate::std_alloc::{format, fs};
+
+        let path = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        let codes = |key: &str| -> Vec<Code> {
+            json[key]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|code| code.as_str().unwrap().into())
+                .collect()
+        };
+        let cb = match json["scheme"].as_str() {
+            Some("raw") => Codebreaker::new_raw(),
+            Some("v1") => Codebreaker::new(),
+            Some("v7") => Codebreaker::new_v7(),
+            scheme => panic!("invalid scheme: {scheme:?}"),
+        };
+        Test {
+            cb,
+            decrypted: codes("decrypted"),
+            encrypted: codes("encrypted"),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_fixtures() {
+        for name in ["v1.json", "v7_default.json"] {
+            let t = load_fixture(name);
+            let (mut enc, mut dec) = (t.cb, t.cb);
+            assert_eq!(enc.encrypt_codes(&t.decrypted), t.encrypted);
+            assert_eq!(dec.decrypt_codes(&t.encrypted), t.decrypted);
+        }
+    }
+
     #[test]
     fn test_encrypt_code() {
         for t in &mut tests() {
@@ -396,6 +1919,105 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_noop_line() {
+        let noop = Code(0, 0);
+
+        // Does not change the scheme of a v1 list
+        let mut state = AutoDecryptState::new();
+        assert_eq!(state.step("9A545CC6 188CBCFB".into()).scheme, Scheme::V1);
+        let out = state.step(noop);
+        assert_eq!((out.code, out.scheme, out.scheme_changed), (noop, Scheme::V1, false));
+
+        // Is decrypted like any other line in a v7 list
+        let mut cb = Codebreaker::new_v7();
+        let mut cb7 = Cb7::default();
+        assert_eq!(cb.auto_decrypt(noop), cb7.decrypt_code(0, 0).into());
+        assert_ne!(cb7.decrypt_code(0, 0), (0, 0));
+    }
+
+    #[test]
+    fn test_decrypt_twice() {
+        // Plain decryption scrambles already decrypted codes
+        let tests = [
+            (
+                Codebreaker::new(),
+                ["2043AFCC 2411FFFF", "201F6024 00000000"],
+                ["2AAD2F78 2411FFFF", "2A64D7AC 00000000"],
+                ["202DA31E 2411FFFF", "20F54F57 00000000"],
+            ),
+            (
+                Codebreaker::new(),
+                ["2AFF014C 2411FFFF", "2A973DBD 00000000"],
+                ["2043AFCC 2411FFFF", "201F6024 00000000"],
+                ["2AAD2F78 2411FFFF", "2A64D7AC 00000000"],
+            ),
+            (
+                Codebreaker::new(),
+                ["B4336FA9 4DFEFB79", "973E0B2A A7D4AF10"],
+                ["BEEFC0DE 00000000", "2096F5B8 000000BE"],
+                ["B458E98B C5222C0E", "2AD76325 000000BE"],
+            ),
+            (
+                Codebreaker::new_raw(),
+                ["2043AFCC 2411FFFF", "201F6024 00000000"],
+                ["2043AFCC 2411FFFF", "201F6024 00000000"],
+                ["2043AFCC 2411FFFF", "201F6024 00000000"],
+            ),
+        ];
+        for (cb, input, once, twice) in tests {
+            let codes = |lines: [&str; 2]| -> Vec<Code> { lines.iter().map(|&line| line.into()).collect() };
+            let mut c = cb;
+            let result: Vec<Code> = codes(input).iter().map(|&code| c.decrypt(code)).collect();
+            assert_eq!(result, codes(once));
+            let mut c = cb;
+            let result: Vec<Code> = result.iter().map(|&code| c.decrypt(code)).collect();
+            assert_eq!(result, codes(twice));
+        }
+
+        // Auto-decrypting the output again returns it unchanged
+        for t in auto_tests() {
+            let mut cb = Codebreaker::new();
+            let result: Vec<Code> = t.output.iter().map(|&code| cb.auto_decrypt(code)).collect();
+            assert_eq!(result, t.output);
+        }
+
+        // Also if the first code looks like v7 without header, unless that
+        // detection is enabled
+        let output: Vec<Code> = vec!["00100044 00000111".into(), "2043AFCC 2411FFFF".into()];
+        let mut cb = Codebreaker::new();
+        let result: Vec<Code> = output.iter().map(|&code| cb.auto_decrypt(code)).collect();
+        assert_eq!(result, output);
+        let mut cb = Codebreaker::new().detect_headerless_v7(true);
+        let result: Vec<Code> = output.iter().map(|&code| cb.auto_decrypt(code)).collect();
+        assert_ne!(result, output);
+    }
+
+    #[test]
+    fn test_encryption_change() {
+        let sentinel = Code(0xffff_ffff, 0x000f_0001);
+        let next: Code = "2096F5B8 000000BE".into();
+        for t in tests() {
+            let mut decrypted = t.decrypted.clone();
+            decrypted.extend([sentinel, next]);
+
+            // The sentinel is encrypted like any other code
+            let mut cb = t.cb;
+            let encrypted: Vec<Code> = decrypted.iter().map(|&code| cb.encrypt(code)).collect();
+            let n = t.decrypted.len();
+            assert_ne!(encrypted[n], sentinel);
+
+            let mut cb = t.cb;
+            let result: Vec<Code> = encrypted.iter().map(|&code| cb.decrypt(code)).collect();
+            assert_eq!(result, decrypted);
+
+            // Auto-decryption recognizes it after decryption
+            let mut cb = t.cb;
+            let result: Vec<Code> = encrypted.iter().map(|&code| cb.auto_decrypt(code)).collect();
+            assert_eq!(result, decrypted);
+        }
+    }
+
     struct AutoTest {
         input: Vec<Code>,
         output: Vec<Code>,
@@ -474,6 +2096,38 @@ mod tests {
                     "2096F5B8 000000BE".into(),
                 ],
             },
+            AutoTest {
+                // v7 encrypted with BEEFC0DF
+                input: vec![
+                    "B4326FA9 1F0AFA2C".into(),
+                    "862316AB C59C5FB1".into(),
+                    "06133B66 95444FF1".into(),
+                ],
+                output: vec![
+                    "BEEFC0DF B16B00B5".into(),
+                    "01234567 89ABCDEF".into(),
+                    "9029BEAC 0C0A9225".into(),
+                ],
+            },
+            AutoTest {
+                // v1 encrypted with no-op lines, then v7
+                input: vec![
+                    "9A545CC6 188CBCFB".into(),
+                    "00000000 00000000".into(),
+                    "2A973DBD 00000000".into(),
+                    "00000000 00000000".into(),
+                    "B4336FA9 4DFEFB79".into(),
+                    "973E0B2A A7D4AF10".into(),
+                ],
+                output: vec![
+                    "9029BEAC 0C0A9225".into(),
+                    "00000000 00000000".into(),
+                    "201F6024 00000000".into(),
+                    "00000000 00000000".into(),
+                    "BEEFC0DE 00000000".into(),
+                    "2096F5B8 000000BE".into(),
+                ],
+            },
         ]
     }
 
@@ -498,43 +2152,770 @@ mod tests {
             }
         }
     }
-}
 
-#[cfg(test)]
-mod code {
-    use crate::std_alloc::{fmt, Vec};
+    #[test]
+    fn test_auto_decrypt_classified() {
+        use LineCategory::*;
+        let categories = [
+            &[Raw, Raw, Raw][..],
+            &[V1, V1, V1],
+            &[Header, V7, V7, V7],
+            &[V1, V1, Header, V7],
+            &[Raw, V1, Header, V7],
+            &[Beefcode, V7, V7],
+            &[V1, Raw, V1, Raw, Header, V7],
+        ];
+        for (t, categories) in auto_tests().iter().zip(categories) {
+            let mut cb = Codebreaker::new();
+            for (i, &code) in t.input.iter().enumerate() {
+                let result = cb.auto_decrypt_classified(code.0, code.1);
+                assert_eq!(result, (t.output[i], categories[i]));
+            }
+        }
+
+        let mut cb = Codebreaker::new();
+        assert_eq!(
+            cb.auto_decrypt_classified(0xbeef_c0de, 0x0000_0000),
+            (Code(0xbeef_c0de, 0x0000_0000), Beefcode)
+        );
+    }
+
+    #[test]
+    fn test_from_scheme_str() {
+        let tests = [
+            ("raw", Ok(Scheme::Raw)),
+            ("v1", Ok(Scheme::V1)),
+            ("v7", Ok(Scheme::V7)),
+            ("RAW", Ok(Scheme::Raw)),
+            ("V7", Ok(Scheme::V7)),
+            ("", Err(ParseSchemeError)),
+            ("v2", Err(ParseSchemeError)),
+            (" v7", Err(ParseSchemeError)),
+        ];
+        for (s, scheme) in tests {
+            assert_eq!(s.parse(), scheme);
+            assert_eq!(Codebreaker::from_scheme_str(s).is_ok(), scheme.is_ok());
+        }
+
+        let code = (0x2043_afcc, 0x2411_ffff);
+        let mut cb = Codebreaker::from_scheme_str("raw").unwrap();
+        assert_eq!(cb.encrypt_code(code.0, code.1), code);
+        let mut cb = Codebreaker::try_from("v1").unwrap();
+        assert_eq!(cb.encrypt_code(code.0, code.1), (0x2aff_014c, 0x2411_ffff));
+        let mut cb = Codebreaker::try_from("v7").unwrap();
+        assert_eq!(cb.state.scheme, Scheme::V7);
+        assert_ne!(cb.encrypt_code(code.0, code.1), code);
+    }
+
+    #[test]
+    fn test_encrypt_code_as() {
+        // Beefcode forced to v1, followed by a v7 code
+        let mut cb = Codebreaker::new();
+        assert_eq!(cb.encrypt_code_as(Scheme::V1, 0xbeef_c0de, 0x0000_0000), V7_HEADER);
+        assert_eq!(cb.state.scheme, Scheme::V7);
+        assert_eq!(
+            cb.encrypt_code_as(Scheme::V7, 0x9029_beac, 0x0c0a_9225),
+            (0xd08f_3a49, 0x0007_8a53)
+        );
+
+        // Forcing v1 within a v7 list does not change the state
+        let mut cb = Codebreaker::new_v7();
+        assert_eq!(
+            cb.encrypt_code_as(Scheme::V1, 0x201f_6024, 0x0000_0000),
+            (0x2a97_3dbd, 0x0000_0000)
+        );
+        assert_eq!(cb.state.scheme, Scheme::V7);
+        assert_eq!(
+            cb.encrypt_code_as(Scheme::V7, 0x2096_f5b8, 0x0000_00be),
+            (0x973e_0b2a, 0xa7d4_af10)
+        );
+        assert_eq!(cb.encrypt_code(0x201f_6024, 0x0000_0000), (0x3818_dde5, 0xe72b_2b16));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_reencrypt_in_place() {
+        // v1 to v7 and back
+        let (v1, v7) = (&auto_tests()[1], &auto_tests()[2]);
+        let mut codes = v1.input.clone();
+        Codebreaker::reencrypt_in_place(&mut codes, Scheme::V7);
+        assert_eq!(codes, v7.input);
+        Codebreaker::reencrypt_in_place(&mut codes, Scheme::V7);
+        assert_eq!(codes, v7.input);
+        Codebreaker::reencrypt_in_place(&mut codes, Scheme::V1);
+        assert_eq!(codes, v1.input);
+        Codebreaker::reencrypt_in_place(&mut codes, Scheme::Raw);
+        assert_eq!(codes, v1.output);
+
+        // v7 with BEEFC0DF to raw drops both beefcode lines
+        let mut codes = auto_tests()[5].input.clone();
+        Codebreaker::reencrypt_in_place(&mut codes, Scheme::Raw);
+        assert_eq!(codes, auto_tests()[5].output[2..]);
+
+        let mut codes = Vec::new();
+        Codebreaker::reencrypt_in_place(&mut codes, Scheme::V1);
+        assert!(codes.is_empty());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_decrypt_dedup() {
+        // Same codes in raw, v1, and v7 encrypted form
+        let input = [
+            (0x9029_beac, 0x0c0a_9225),
+            (0x201f_6024, 0x0000_0000),
+            (0x2a97_3dbd, 0x0000_0000),
+            (0xb433_6fa9, 0x4dfe_fb79),
+            (0xd08f_3a49, 0x0007_8a53),
+            (0x3818_dde5, 0xe72b_2b16),
+            (0x973e_0b2a, 0xa7d4_af10),
+        ];
+        let output: Vec<Code> = vec![
+            "9029BEAC 0C0A9225".into(),
+            "201F6024 00000000".into(),
+            "BEEFC0DE 00000000".into(),
+            "2096F5B8 000000BE".into(),
+        ];
+        let mut cb = Codebreaker::new();
+        assert_eq!(cb.decrypt_dedup(&input), output);
+    }
+
+    #[test]
+    fn test_finish() {
+        let tests: [(&[(u32, u32)], _); 5] = [
+            (&[], Ok(())),
+            (&[(0x2043_afcc, 0x2411_ffff)], Ok(())),
+            // Multi-address write, complete and truncated
+            (&[(0x4031_789a, 0x0004_0001), (0x0000_0063, 0x0000_0000)], Ok(())),
+            (&[(0x4031_789a, 0x0004_0001)], Err(FinalizeError::IncompleteCommand(1))),
+            // BEEFC0DF B16B00B5 without extra seed line
+            (&[(0xb432_6fa9, 0x1f0a_fa2c)], Err(FinalizeError::DanglingBeefcodf)),
+        ];
+        for (codes, result) in tests {
+            let mut cb = Codebreaker::new();
+            for &(addr, val) in codes {
+                cb.auto_decrypt_code(addr, val);
+            }
+            assert_eq!(cb.finish(), result);
+            assert_eq!(cb.finish(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_decrypt_tokens() {
+        // Tokenizer for a binary format: 8 bytes per code, big-endian
+        #[derive(Debug, PartialEq)]
+        struct Truncated(usize);
+
+        for t in &auto_tests() {
+            let mut bytes = Vec::new();
+            for code in &t.input {
+                bytes.extend_from_slice(&code.0.to_be_bytes());
+                bytes.extend_from_slice(&code.1.to_be_bytes());
+            }
+            bytes.extend_from_slice(&[0xff; 3]);
+            let tokens = bytes.chunks(8).map(|chunk| {
+                let word = u64::from_be_bytes(chunk.try_into().map_err(|_| Truncated(chunk.len()))?);
+                Ok(((word >> 32) as u32, word as u32))
+            });
+
+            let mut cb = Codebreaker::new();
+            let output: Vec<_> = cb.decrypt_tokens(tokens).collect();
+            let mut expected: Vec<_> = t.output.iter().map(|&code| Ok(code.as_tuple())).collect();
+            expected.push(Err(Truncated(3)));
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[test]
+    fn test_default_v7() {
+        let (a, b) = (Codebreaker::DEFAULT_V7, Codebreaker::new_v7());
+        assert_eq!(a.state.cb7, b.state.cb7);
+        assert_eq!(a.state.scheme, b.state.scheme);
+        assert_eq!(a.state.code_lines, b.state.code_lines);
+        assert_eq!(a.state.passthrough_unknown, b.state.passthrough_unknown);
+        assert_eq!(a.state.detect_headerless_v7, b.state.detect_headerless_v7);
+        assert_eq!(a.state.started, b.state.started);
+        assert_eq!((a.raw, a.strict), (b.raw, b.strict));
+
+        let t = &tests()[2];
+        let mut cb = Codebreaker::DEFAULT_V7;
+        for (i, &code) in t.encrypted.iter().enumerate() {
+            assert_eq!(cb.decrypt(code), t.decrypted[i]);
+        }
+    }
+
+    #[test]
+    fn test_new_raw() {
+        for t in &tests() {
+            let mut cb = Codebreaker::new_raw();
+            for &code in &t.decrypted {
+                assert_eq!(cb.encrypt_code(code.0, code.1), code.as_tuple());
+            }
+            let mut cb = Codebreaker::new_raw();
+            for &code in &t.decrypted {
+                assert_eq!(cb.decrypt_code(code.0, code.1), code.as_tuple());
+            }
+            assert_eq!(cb.state.scheme == Scheme::V7, t.decrypted.iter().any(Code::is_beefcode));
+        }
+    }
+
+    #[test]
+    fn test_fork_fresh() {
+        let mut cb = Codebreaker::new();
+        cb.auto_decrypt_code(0xb432_6fa9, 0x1f0a_fa2c); // BEEFC0DF B16B00B5
+        let (mut copy, mut fork) = (cb, cb.fork_fresh());
+
+        assert_eq!(copy.state.scheme, Scheme::V7);
+        assert_eq!(fork.state.scheme, Scheme::V7);
+        assert_eq!(copy.state.code_lines, 1);
+        assert_eq!(fork.state.code_lines, 0);
+        assert!(copy.state.started);
+        assert!(!fork.state.started);
+        assert!(copy.state.cb7.finish().is_err());
+        assert!(fork.state.cb7.finish().is_ok());
+    }
+
+    #[test]
+    fn test_encrypt_code_checked() {
+        for t in &mut tests() {
+            for (i, &code) in t.decrypted.iter().enumerate() {
+                let result: Code = t.cb.encrypt_code_checked(code.0, code.1).unwrap().into();
+                assert_eq!(result, t.encrypted[i]);
+            }
+        }
+
+        let mut cb = Codebreaker::new();
+        let before = cb;
+        assert_eq!(
+            cb.encrypt_code_checked(0x2a97_3dbd, 0x0000_0000),
+            Err(EncryptError::LooksAlreadyEncrypted)
+        );
+        assert_eq!(cb.state.scheme, before.state.scheme);
+    }
+
+    #[test]
+    fn test_encrypt_code_with_effect() {
+        let mut cb = Codebreaker::new();
+        let tests = [
+            ("2043AFCC 2411FFFF", "2AFF014C 2411FFFF", LineEffect::None),
+            ("BEEFC0DE 00000000", "B4336FA9 4DFEFB79", LineEffect::EnteredV7),
+            ("2096F5B8 000000BE", "973E0B2A A7D4AF10", LineEffect::None),
+            ("BEEFC0DE 00000000", "8787C575 1AC4C1B4", LineEffect::Reseeded),
+        ];
+        for (decrypted, encrypted, effect) in tests {
+            let code: Code = decrypted.into();
+            let (result, e) = cb.encrypt_code_with_effect(code.0, code.1);
+            assert_eq!(Code::from(result), encrypted.into());
+            assert_eq!(e, effect);
+        }
+    }
+
+    #[test]
+    fn test_decrypt_next() {
+        for t in &mut tests() {
+            for (i, &code) in t.encrypted.iter().enumerate() {
+                let result: Code = t.cb.decrypt_next((code.0, code.1)).into();
+                assert_eq!(result, t.decrypted[i]);
+            }
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_encrypt_list() {
+        let tuples = |codes: &[Code]| -> Vec<(u32, u32)> { codes.iter().map(Code::as_tuple).collect() };
+
+        // v1
+        let t = &tests()[4];
+        assert_eq!(encrypt_list(&tuples(&t.decrypted), Scheme::V1), tuples(&t.encrypted));
+        assert_eq!(encrypt_list(&tuples(&t.decrypted), Scheme::Raw), tuples(&t.decrypted));
+
+        // v7 without beefcode gets the header
+        let t = &tests()[2];
+        let mut expected = vec![V7_HEADER];
+        expected.extend(tuples(&t.encrypted));
+        assert_eq!(encrypt_list(&tuples(&t.decrypted), Scheme::V7), expected);
 
-    #[derive(Copy, Clone, PartialEq, Eq)]
-    pub struct Code(pub u32, pub u32);
+        // v7 starting with BEEFC0DF
+        let t = &tests()[1];
+        assert_eq!(encrypt_list(&tuples(&t.decrypted), Scheme::V7), tuples(&t.encrypted));
 
-    impl From<(u32, u32)> for Code {
-        fn from(t: (u32, u32)) -> Self {
-            Self(t.0, t.1)
+        assert!(encrypt_list(&[], Scheme::V1).is_empty());
+        assert_eq!(encrypt_list(&[], Scheme::V7), [V7_HEADER]);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_encrypt_list_detailed() {
+        let tuples = |codes: &[Code]| -> Vec<(u32, u32)> { codes.iter().map(Code::as_tuple).collect() };
+        let cases = [
+            (2, Scheme::V7, true),
+            (1, Scheme::V7, false),
+            (4, Scheme::V1, false),
+            (4, Scheme::Raw, false),
+        ];
+        for (i, scheme, header_inserted) in cases {
+            let codes = tuples(&tests()[i].decrypted);
+            let result = encrypt_list_detailed(&codes, scheme);
+            assert_eq!(result.header_inserted, header_inserted);
+            assert_eq!(result.code_lines(), codes.len());
+            assert_eq!(tuples(&result.lines), encrypt_list(&codes, scheme));
+        }
+
+        let result = encrypt_list_detailed(&[], Scheme::V7);
+        assert_eq!(result.lines, [Code::from(V7_HEADER)]);
+        assert!(result.header_inserted);
+        assert_eq!(result.code_lines(), 0);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_decrypt_list_with_progress() {
+        let tests = [
+            (0, vec![]),
+            (1, vec![1]),
+            (PROGRESS_INTERVAL, vec![PROGRESS_INTERVAL]),
+            (
+                2 * PROGRESS_INTERVAL + 10,
+                vec![PROGRESS_INTERVAL, 2 * PROGRESS_INTERVAL, 2 * PROGRESS_INTERVAL + 10],
+            ),
+        ];
+        let t = &auto_tests()[4];
+        for (len, expected) in tests {
+            let codes: Vec<(u32, u32)> = t.input.iter().map(Code::as_tuple).cycle().take(len).collect();
+            let mut reports = Vec::new();
+            let output = decrypt_list_with_progress(&codes, |n| reports.push(n));
+            assert_eq!(reports, expected);
+
+            let mut cb = Codebreaker::new();
+            let expected: Vec<(u32, u32)> = codes.iter().map(|c| cb.auto_decrypt_code(c.0, c.1)).collect();
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[test]
+    fn test_encrypt_range() {
+        // v7 with header and a beefcode in the middle of the list
+        let decrypted: Vec<Code> = vec![
+            "BEEFC0DE 00000000".into(),
+            "9029BEAC 0C0A9225".into(),
+            "201F6024 00000000".into(),
+            "BEEFC0DE DEADFACE".into(),
+            "2096F5B8 000000BE".into(),
+            "2043AFCC 2411FFFF".into(),
+        ];
+        let mut cb = Codebreaker::new();
+        let encrypted: Vec<Code> = decrypted.iter().map(|&code| cb.encrypt(code)).collect();
+
+        for (decrypted, encrypted) in [(&decrypted, &encrypted), (&tests()[0].decrypted, &tests()[0].encrypted)] {
+            let n = decrypted.len();
+            for start in 0..=n {
+                for end in start..=n {
+                    let mut codes: Vec<(u32, u32)> = encrypted.iter().map(Code::as_tuple).collect();
+                    for i in start..end {
+                        codes[i] = decrypted[i].as_tuple();
+                    }
+                    encrypt_range(&mut codes, start..end);
+                    let result: Vec<Code> = codes.into_iter().map(Code::from).collect();
+                    assert_eq!(&result, encrypted);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_detect_scheme() {
+        let tuples = |codes: &[Code]| -> Vec<(u32, u32)> { codes.iter().map(Code::as_tuple).collect() };
+        let auto = auto_tests();
+        let tests = [
+            (tuples(&auto[0].input), Scheme::Raw),
+            (tuples(&auto[1].input), Scheme::V1),
+            (tuples(&auto[2].input), Scheme::V7),
+            (tuples(&auto[3].input), Scheme::V7),
+            (tuples(&auto[5].input), Scheme::V7),
+            // Without header
+            (tuples(&tests()[2].encrypted), Scheme::V7),
+            (tuples(&tests()[4].encrypted), Scheme::V1),
+            (tuples(&tests()[4].decrypted), Scheme::Raw),
+            // Already decrypted with raw beefcode
+            (tuples(&auto_tests()[5].output), Scheme::Raw),
+            (vec![(0xbeef_c0de, 0), (0x2096_f5b8, 0xbe)], Scheme::Raw),
+            // Starts with a raw code that looks like v7 without header
+            (
+                [&[(0x0010_0044, 0x0000_0111)], &tuples(&tests()[4].decrypted)[..]].concat(),
+                Scheme::Raw,
+            ),
+            (vec![], Scheme::Raw),
+        ];
+        for (codes, scheme) in tests {
+            assert_eq!(detect_scheme(&codes), scheme);
         }
     }
 
-    impl From<&str> for Code {
-        fn from(s: &str) -> Self {
-            let t: Vec<u32> = s
-                .splitn(2, ' ')
-                .map(|v| u32::from_str_radix(v, 16).expect("invalid code format"))
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_split_by_scheme() {
+        // raw, v1, and v7 encrypted
+        let t = &auto_tests()[4];
+        let input: Vec<(u32, u32)> = t.input.iter().map(Code::as_tuple).collect();
+        let blocks = split_by_scheme(&input);
+        assert_eq!(
+            blocks,
+            [
+                (Scheme::Raw, t.output[..1].to_vec()),
+                (Scheme::V1, t.output[1..2].to_vec()),
+                (Scheme::V7, t.output[2..].to_vec()),
+            ]
+        );
+
+        // Concatenating the blocks gives the auto-decrypted list
+        for t in &auto_tests() {
+            let input: Vec<(u32, u32)> = t.input.iter().map(Code::as_tuple).collect();
+            let codes: Vec<Code> = split_by_scheme(&input)
+                .into_iter()
+                .flat_map(|(_, codes)| codes)
                 .collect();
+            assert_eq!(codes, t.output);
+        }
+        assert!(split_by_scheme(&[]).is_empty());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_zip_transform() {
+        for t in &auto_tests() {
+            let input: Vec<(u32, u32)> = t.input.iter().map(Code::as_tuple).collect();
+            let pairs = zip_transform(&input);
+            let left: Vec<Code> = pairs.iter().map(|p| p.0).collect();
+            let right: Vec<Code> = pairs.iter().map(|p| p.1).collect();
+            assert_eq!(left, t.input);
+            assert_eq!(right, t.output);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_encrypt_decrypt_codes() {
+        for t in &tests() {
+            let (mut enc, mut dec) = (t.cb, t.cb);
+            assert_eq!(enc.encrypt_codes(&t.decrypted), t.encrypted);
+            assert_eq!(dec.decrypt_codes(&t.encrypted), t.decrypted);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_decrypt_ordered() {
+        for t in &tests() {
+            let labeled: Vec<(usize, Code)> = t.encrypted.iter().copied().enumerate().collect();
+            let mut cb = t.cb;
+            let result = cb.decrypt_ordered(&labeled);
+            assert_eq!(result.len(), t.decrypted.len());
+            for (i, &(label, code)) in result.iter().enumerate() {
+                assert_eq!(label, i);
+                assert_eq!(code, t.decrypted[i]);
+            }
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_auto_decrypt_codes() {
+        for t in &auto_tests() {
+            let mut cb = Codebreaker::new();
+            assert_eq!(cb.auto_decrypt_codes(&t.input), t.output);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_decrypt_report() {
+        for (t, transformed) in auto_tests().iter().zip([false, true, true, true]) {
+            let input: Vec<(u32, u32)> = t.input.iter().map(Code::as_tuple).collect();
+            let mut cb = Codebreaker::new();
+            let report = cb.decrypt_report(&input);
+            for (i, &(code, changed)) in report.iter().enumerate() {
+                assert_eq!(code, t.output[i]);
+                assert_eq!(changed, transformed);
+            }
+        }
+
+        // raw, v1, and v7 encrypted
+        let t = &auto_tests()[4];
+        let input: Vec<(u32, u32)> = t.input.iter().map(Code::as_tuple).collect();
+        let report = Codebreaker::new().decrypt_report(&input);
+        assert_eq!(
+            report.iter().map(|r| r.1).collect::<Vec<_>>(),
+            [false, true, true, true]
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_decrypt_annotated() {
+        use alloc::string::ToString;
+
+        for t in &auto_tests() {
+            let comment = Line::Comment("comment".to_string());
+            let annotate = |codes: &[Code]| -> Vec<Line> {
+                codes
+                    .iter()
+                    .flat_map(|&code| [comment.clone(), Line::Code(code)])
+                    .chain([comment.clone()])
+                    .collect()
+            };
+
+            let mut cb = Codebreaker::new();
+            assert_eq!(cb.decrypt_annotated(&annotate(&t.input)), annotate(&t.output));
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_process_line() {
+        use alloc::string::ToString;
+
+        for t in &auto_tests() {
+            let mut cb = Codebreaker::new();
+            for (i, code) in t.input.iter().enumerate() {
+                assert_eq!(cb.process_line(&code.to_string()), LineResult::Code(t.output[i]));
+            }
+        }
 
-            Self(t[0], t[1])
+        let mut cb = Codebreaker::new();
+        let tests = &[
+            ("", LineResult::Blank),
+            (" \t", LineResult::Blank),
+            ("Mastercode", LineResult::Comment("Mastercode".to_string())),
+            (
+                "  // Infinite Ammo ",
+                LineResult::Comment("// Infinite Ammo".to_string()),
+            ),
+            ("2043AFCC", LineResult::Comment("2043AFCC".to_string())),
+            (
+                "2043AFCC 2411FFFF0",
+                LineResult::Comment("2043AFCC 2411FFFF0".to_string()),
+            ),
+            ("Dead Face", LineResult::Comment("Dead Face".to_string())),
+            ("Cafe", LineResult::Comment("Cafe".to_string())),
+            (
+                "0x2043AFCC0 0x2411FFFF",
+                LineResult::ParseError(CodeParseError::InvalidWord),
+            ),
+            (
+                " 0x2043AFCC 0x2411FFFF ",
+                LineResult::Code(Code(0x2043_afcc, 0x2411_ffff)),
+            ),
+            ("2043afcc 2411ffff", LineResult::Code(Code(0x2043_afcc, 0x2411_ffff))),
+        ];
+        for t in tests {
+            assert_eq!(cb.process_line(t.0), t.1);
+        }
+
+        // Comments made of hex letters do not change the state
+        for t in &auto_tests() {
+            let mut cb = Codebreaker::new();
+            for (i, code) in t.input.iter().enumerate() {
+                assert_eq!(
+                    cb.process_line("Dead Face"),
+                    LineResult::Comment("Dead Face".to_string())
+                );
+                assert_eq!(cb.process_line(&code.to_string()), LineResult::Code(t.output[i]));
+            }
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_decrypt_bytes() {
+        let to_bytes = |codes: &[Code]| -> Vec<u8> {
+            codes
+                .iter()
+                .flat_map(|c| [c.0.to_le_bytes(), c.1.to_le_bytes()].concat())
+                .collect()
+        };
+
+        for t in &auto_tests() {
+            let mut cb = Codebreaker::new();
+            let result = cb.decrypt_bytes(&to_bytes(&t.input)).unwrap();
+            assert_eq!(result, to_bytes(&t.output));
+        }
+
+        let mut cb = Codebreaker::new();
+        assert_eq!(cb.decrypt_bytes(&[0; 7]), Err(LenError(7)));
+        assert_eq!(cb.decrypt_bytes(&[]), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_peek_scheme() {
+        let schemes = [
+            vec![Scheme::Raw, Scheme::Raw, Scheme::Raw],
+            vec![Scheme::V1, Scheme::V1, Scheme::V1],
+            vec![Scheme::V1, Scheme::V7, Scheme::V7, Scheme::V7],
+            vec![Scheme::V1, Scheme::V1, Scheme::V1, Scheme::V7],
+            vec![Scheme::Raw, Scheme::V1, Scheme::V1, Scheme::V7],
+        ];
+        for (t, schemes) in auto_tests().iter().zip(schemes.iter()) {
+            let mut cb = Codebreaker::new();
+            for (i, &code) in t.input.iter().enumerate() {
+                let before = (cb.state.scheme, cb.state.code_lines, cb.state.started);
+                assert_eq!(cb.peek_scheme(code.0), schemes[i]);
+                assert_eq!((cb.state.scheme, cb.state.code_lines, cb.state.started), before);
+                cb.auto_decrypt_code(code.0, code.1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_auto_decrypt_code_command3() {
+        // 8/16-bit inc/dec use one line, 32-bit inc/dec (3040/3050) use two
+        let tests = [
+            ("30000001 0043AFCC", 0),
+            ("30100001 0043AFCC", 0),
+            ("30200001 0043AFCC", 0),
+            ("30300001 0043AFCC", 0),
+            ("30400000 0043AFCC", 1),
+            ("30500000 0043AFCC", 1),
+        ];
+        for (code, lines) in tests {
+            let code: Code = code.into();
+
+            let mut cb = Codebreaker::new();
+            assert_eq!(cb.auto_decrypt_code(code.0, code.1), code.as_tuple());
+            assert_eq!(cb.state.code_lines, lines);
+
+            // The bit 0x00400000 does not survive v1 encryption
+            let (addr, val) = cb1::encrypt_code(code.0, code.1);
+            let mut cb = Codebreaker::new();
+            assert_eq!(cb.auto_decrypt_code(addr, val), code.as_tuple());
+            assert_eq!(cb.state.code_lines, lines);
+        }
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn test_decrypt_heapless() {
+        for t in &auto_tests() {
+            let mut codes: heapless::Vec<(u32, u32), 8> = t.input.iter().map(Code::as_tuple).collect();
+            let mut cb = Codebreaker::new();
+            cb.decrypt_heapless(&mut codes);
+            assert!(codes.iter().map(|&c| Code::from(c)).eq(t.output.iter().copied()));
+        }
+    }
+
+    #[test]
+    fn test_lines_remaining() {
+        let input: Vec<Code> = vec![
+            "4031789A 00040001".into(), // multi-address write, two lines
+            "00000063 00000000".into(),
+            "201F6024 00000000".into(),
+        ];
+        for encrypt in [false, true] {
+            let mut cb = Codebreaker::new();
+            assert_eq!(cb.lines_remaining(), 0);
+            for (code, remaining) in input.iter().zip([1, 0, 0]) {
+                let (addr, val) = if encrypt {
+                    cb1::encrypt_code(code.0, code.1)
+                } else {
+                    code.as_tuple()
+                };
+                cb.auto_decrypt_code(addr, val);
+                assert_eq!(cb.lines_remaining(), remaining);
+            }
+        }
+    }
+
+    #[test]
+    fn test_auto_decrypt_code_checked() {
+        for t in &auto_tests() {
+            let mut cb = Codebreaker::new().strict(true);
+            for (i, &code) in t.input.iter().enumerate() {
+                let result: Code = cb.auto_decrypt_code_checked(code.0, code.1).unwrap().into();
+                assert_eq!(result, t.output[i]);
+            }
+        }
+
+        let garbage = [(0x8000_0000, 0x1234_5678), (0xa123_4567, 0x89ab_cdef)];
+        for (addr, val) in garbage {
+            let mut cb = Codebreaker::new().strict(true);
+            let before = cb;
+            assert_eq!(
+                cb.auto_decrypt_code_checked(addr, val),
+                Err(UnknownCommand(cb1::command(addr)))
+            );
+            assert_eq!(cb.state.started, before.state.started);
+
+            let mut cb = Codebreaker::new();
+            assert_eq!(cb.auto_decrypt_code_checked(addr, val), Ok((addr, val)));
+        }
+
+        // Continuation lines are not checked
+        let mut cb = Codebreaker::new().strict(true);
+        assert!(cb.auto_decrypt_code_checked(0x4031_789a, 0x0004_0001).is_ok());
+        assert!(cb.auto_decrypt_code_checked(0x8000_0000, 0x0000_0000).is_ok());
+    }
+
+    #[test]
+    fn test_auto_decrypt_code_header() {
+        // v7 encrypted, with and without header
+        let t = &auto_tests()[2];
+        for skip in 0..2 {
+            let mut cb = Codebreaker::new().detect_headerless_v7(true);
+            for (i, &code) in t.input.iter().enumerate().skip(skip) {
+                let result: Code = cb.auto_decrypt_code(code.0, code.1).into();
+                assert_eq!(result, t.output[i]);
+            }
         }
     }
 
-    // Implements ToString
-    impl fmt::Display for Code {
-        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            write!(f, "{:08X} {:08X}", self.0, self.1)
+    #[test]
+    fn test_auto_decrypt_code_headerless_raw() {
+        // Raw codes that look like v7 without header, followed by a raw code
+        let next = (0x2043_afcc, 0x2411_ffff);
+        let tests = [
+            (0x0010_0044, 0x0000_0111), // 8-bit write with stray upper byte
+            (0x1010_0040, 0x0009_1234), // 16-bit write with stray upper half-word
+            (0x8010_001c, 0x0000_0063), // unknown command
+        ];
+        for code in tests {
+            let mut cb = Codebreaker::new();
+            assert_eq!(cb.auto_decrypt_code(code.0, code.1), code);
+            assert_eq!(cb.auto_decrypt_code(next.0, next.1), next);
+
+            let mut cb = Codebreaker::new().detect_headerless_v7(true);
+            assert_ne!(cb.auto_decrypt_code(code.0, code.1), code);
         }
+
+        let code = tests[2];
+        let mut cb = Codebreaker::new().detect_headerless_v7(true).passthrough_unknown(true);
+        assert_eq!(cb.auto_decrypt_code(code.0, code.1), code);
+        assert_eq!(cb.auto_decrypt_code(next.0, next.1), next);
     }
 
-    // Used by assert_eq!
-    impl fmt::Debug for Code {
-        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            write!(f, "{self}")
+    #[test]
+    fn test_auto_decrypt_code_passthrough_unknown() {
+        let input: Vec<Code> = vec![
+            "9A545CC6 188CBCFB".into(),
+            "8A123456 12345678".into(),
+            "2A973DBD 00000000".into(),
+            "AE000000 DEADBEEF".into(),
+        ];
+        let output: Vec<Code> = vec![
+            "9029BEAC 0C0A9225".into(),
+            "8A123456 12345678".into(),
+            "201F6024 00000000".into(),
+            "AE000000 DEADBEEF".into(),
+        ];
+
+        let mut cb = Codebreaker::new().passthrough_unknown(true);
+        for (i, &code) in input.iter().enumerate() {
+            let result: Code = cb.auto_decrypt_code(code.0, code.1).into();
+            assert_eq!(result, output[i]);
         }
+
+        let mut cb = Codebreaker::new();
+        let result: Code = cb.auto_decrypt_code(input[1].0, input[1].1).into();
+        assert_ne!(result, output[1]);
     }
 }
@@ -0,0 +1,18 @@
+//! Re-exports of the most commonly used types.
+//!
+//! # Example
+//! ```
+//! use codebreaker::prelude::*;
+//!
+//! let mut cb = Codebreaker::new();
+//! let code: Code = cb.auto_decrypt_code(0x2A973DBD, 0x00000000).into();
+//! assert_eq!(code, Code(0x201F6024, 0x00000000));
+//! ```
+
+pub use crate::cb7::Cb7;
+pub use crate::{
+    AutoDecryptState, Code, CodeParseError, Codebreaker, EncryptError, FinalizeError, LenError, LineCategory,
+    LineEffect, LineKind, MemRegion, ParseSchemeError, Scheme,
+};
+#[cfg(feature = "alloc")]
+pub use crate::{CodeList, EncryptResult, Line, LineResult};
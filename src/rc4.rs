@@ -1,7 +1,11 @@
-// Implementation of the stream cipher RC4
-// Based on https://github.com/DaGenix/rust-crypto/blob/master/src/rc4.rs
+//! Implementation of the stream cipher RC4.
+//!
+//! Based on <https://github.com/DaGenix/rust-crypto/blob/master/src/rc4.rs>
 
-#[derive(Clone, Copy)]
+use core::fmt;
+
+/// An RC4 cipher instance.
+#[derive(Debug, Clone, Copy)]
 pub struct Rc4 {
     i: u8,
     j: u8,
@@ -9,35 +13,234 @@ pub struct Rc4 {
 }
 
 impl Rc4 {
-    #[allow(clippy::needless_range_loop)]
-    pub fn new(key: &[u8]) -> Self {
-        assert!(!key.is_empty() && key.len() <= 256);
+    /// Returns a new cipher initialized with the given key.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::rc4::Rc4;
+    ///
+    /// let mut rc4 = Rc4::new(b"Key");
+    /// let mut buf = *b"Plaintext";
+    /// rc4.crypt(&mut buf);
+    /// assert_eq!(buf, [0xbb, 0xf3, 0x16, 0xe8, 0xd9, 0x40, 0xaf, 0x0a, 0xd3]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is empty or longer than 256 bytes. Use
+    /// [`try_new`](#method.try_new) for keys that come from untrusted input.
+    pub const fn new(key: &[u8]) -> Self {
+        match Self::try_new(key) {
+            Ok(rc4) => rc4,
+            Err(_) => panic!("invalid RC4 key length"),
+        }
+    }
+
+    /// Returns a new cipher initialized with the given key, or an error if
+    /// the key is empty or longer than 256 bytes.
+    ///
+    /// # Example
+    /// ```
+    /// use codebreaker::rc4::{Rc4, Rc4KeyError};
+    ///
+    /// assert!(Rc4::try_new(b"Key").is_ok());
+    /// assert_eq!(Rc4::try_new(b"").unwrap_err(), Rc4KeyError::Empty);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Rc4KeyError::Empty`] or [`Rc4KeyError::TooLong`] if the key
+    /// length is out of range.
+    pub const fn try_new(key: &[u8]) -> Result<Self, Rc4KeyError> {
+        if key.is_empty() {
+            return Err(Rc4KeyError::Empty);
+        }
+        if key.len() > 256 {
+            return Err(Rc4KeyError::TooLong(key.len()));
+        }
+        // Loops instead of iterators to allow const evaluation
         let mut state = [0; 256];
-        for i in 0..256 {
+        let mut i = 0;
+        while i < 256 {
             state[i] = i as u8;
+            i += 1;
         }
         let mut j: u8 = 0;
-        for i in 0..256 {
+        i = 0;
+        while i < 256 {
             j = j.wrapping_add(state[i]).wrapping_add(key[i % key.len()]);
-            state.swap(i, j as usize);
+            let tmp = state[i];
+            state[i] = state[j as usize];
+            state[j as usize] = tmp;
+            i += 1;
         }
-        Self { i: 0, j: 0, state }
+        Ok(Self { i: 0, j: 0, state })
     }
 
-    pub fn crypt(&mut self, buf: &mut [u8]) {
-        for i in buf.iter_mut() {
+    /// Returns a new cipher that discards the first `n` bytes of keystream,
+    /// also known as RC4-drop\[n\].
+    ///
+    /// CodeBreaker itself uses plain RC4.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is empty or longer than 256 bytes.
+    pub fn new_with_drop(key: &[u8], n: usize) -> Self {
+        let mut rc4 = Self::new(key);
+        let mut buf = [0; 256];
+        let mut left = n;
+        while left > 0 {
+            let len = left.min(buf.len());
+            rc4.crypt(&mut buf[..len]);
+            left -= len;
+        }
+        rc4
+    }
+
+    /// Encrypts or decrypts the given buffer in place.
+    pub const fn crypt(&mut self, buf: &mut [u8]) {
+        let mut k = 0;
+        while k < buf.len() {
             self.i = self.i.wrapping_add(1);
             self.j = self.j.wrapping_add(self.state[self.i as usize]);
-            self.state.swap(self.i as usize, self.j as usize);
+            let tmp = self.state[self.i as usize];
+            self.state[self.i as usize] = self.state[self.j as usize];
+            self.state[self.j as usize] = tmp;
             let j = self.state[self.i as usize].wrapping_add(self.state[self.j as usize]);
-            *i ^= self.state[j as usize];
+            buf[k] ^= self.state[j as usize];
+            k += 1;
         }
     }
+
+    /// Like [`crypt`](#method.crypt), but without memory accesses that depend
+    /// on secret state.
+    ///
+    /// `crypt` has no data-dependent branches, but its table lookups are
+    /// indexed by secret state and may leak it through cache timing. Here,
+    /// every lookup scans the whole table instead, which makes this method
+    /// much slower.
+    ///
+    /// Note that this is a best effort: the key schedule in
+    /// [`new`](#method.new) still performs secret-indexed lookups, and the
+    /// compiler gives no guarantees about the generated code. RC4 is not a
+    /// good choice for protecting sensitive data in any case.
+    pub fn crypt_ct(&mut self, buf: &mut [u8]) {
+        for b in buf.iter_mut() {
+            self.i = self.i.wrapping_add(1);
+            let si = self.state[self.i as usize];
+            self.j = self.j.wrapping_add(si);
+            let sj = ct_load(&self.state, self.j);
+            self.state[self.i as usize] = sj;
+            ct_store(&mut self.state, self.j, si);
+            *b ^= ct_load(&self.state, si.wrapping_add(sj));
+        }
+    }
+
+    /// Encrypts or decrypts `input` into `output`, leaving `input` untouched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `input` and `output` differ in length.
+    pub fn crypt_to(&mut self, input: &[u8], output: &mut [u8]) {
+        assert_eq!(input.len(), output.len());
+        output.copy_from_slice(input);
+        self.crypt(output);
+    }
+}
+
+/// A stream cipher that can be used by [`Cb7`](crate::cb7::Cb7) in place of
+/// [`Rc4`], e.g. an instrumented RC4 that logs the keystream.
+///
+/// Encrypting and decrypting must be the same operation, as with RC4.
+///
+/// # Example
+/// ```
+/// use codebreaker::cb7::Cb7;
+/// use codebreaker::rc4::{Rc4, StreamCipher};
+///
+/// // Counts the keystream bytes used by Cb7
+/// static BYTES: core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(0);
+///
+/// struct CountingRc4(Rc4);
+///
+/// impl StreamCipher for CountingRc4 {
+///     fn new(key: &[u8]) -> Self {
+///         Self(Rc4::new(key))
+///     }
+///
+///     fn crypt(&mut self, buf: &mut [u8]) {
+///         BYTES.fetch_add(buf.len(), core::sync::atomic::Ordering::Relaxed);
+///         self.0.crypt(buf);
+///     }
+/// }
+///
+/// let mut cb7 = Cb7::<CountingRc4>::with_cipher();
+/// cb7.beefcode(0xBEEFC0DE, 0x00000000);
+/// assert_eq!(cb7.encrypt_code(0x2043AFCC, 0x2411FFFF), (0x397951B0, 0x41569FE0));
+/// assert_eq!(BYTES.load(core::sync::atomic::Ordering::Relaxed), 5 * (256 + 20) + 8);
+/// ```
+pub trait StreamCipher {
+    /// Returns a new cipher initialized with the given key.
+    fn new(key: &[u8]) -> Self;
+
+    /// Encrypts or decrypts the given buffer in place.
+    fn crypt(&mut self, buf: &mut [u8]);
+}
+
+impl StreamCipher for Rc4 {
+    fn new(key: &[u8]) -> Self {
+        Self::new(key)
+    }
+
+    fn crypt(&mut self, buf: &mut [u8]) {
+        Self::crypt(self, buf);
+    }
+}
+
+/// Error returned by [`Rc4::try_new`] if the key length is out of range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rc4KeyError {
+    /// The key is empty.
+    Empty,
+    /// The key is longer than 256 bytes; holds the actual length.
+    TooLong(usize),
+}
+
+impl fmt::Display for Rc4KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("RC4 key is empty"),
+            Self::TooLong(len) => write!(f, "RC4 key length {len} exceeds 256 bytes"),
+        }
+    }
+}
+
+impl core::error::Error for Rc4KeyError {}
+
+// Returns 0xff if a == b, else 0x00, without branching
+const fn ct_eq_mask(a: u8, b: u8) -> u8 {
+    let diff = (a ^ b) as u32;
+    (diff.wrapping_sub(1) >> 8) as u8
+}
+
+fn ct_load(state: &[u8; 256], idx: u8) -> u8 {
+    state
+        .iter()
+        .enumerate()
+        .fold(0, |acc, (k, &v)| acc | (v & ct_eq_mask(k as u8, idx)))
+}
+
+fn ct_store(state: &mut [u8; 256], idx: u8, val: u8) {
+    for (k, v) in state.iter_mut().enumerate() {
+        let mask = ct_eq_mask(k as u8, idx);
+        *v = (*v & !mask) | (val & mask);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::std_alloc::vec;
     #[cfg(feature = "std")]
     use pretty_assertions::assert_eq;
 
@@ -78,4 +281,126 @@ mod tests {
             assert_eq!(buf, t.output);
         }
     }
+
+    #[test]
+    fn test_crypt_ct() {
+        for t in wikipedia_tests() {
+            let mut rc4 = Rc4::new(t.key.as_bytes());
+            let mut buf = t.input.as_bytes().to_vec();
+            rc4.crypt_ct(&mut buf);
+            assert_eq!(buf, t.output);
+        }
+
+        // Compare longer keystreams, which also covers i == j
+        let mut rc4 = Rc4::new(b"Secret");
+        let mut rc4_ct = rc4;
+        let mut buf = vec![0; 4096];
+        let mut buf_ct = buf.clone();
+        rc4.crypt(&mut buf);
+        rc4_ct.crypt_ct(&mut buf_ct);
+        assert_eq!(buf, buf_ct);
+    }
+
+    #[test]
+    fn test_crypt_to() {
+        for t in wikipedia_tests() {
+            let mut rc4 = Rc4::new(t.key.as_bytes());
+            let mut output = vec![0; t.input.len()];
+            rc4.crypt_to(t.input.as_bytes(), &mut output);
+            assert_eq!(output, t.output);
+
+            let mut rc4 = Rc4::new(t.key.as_bytes());
+            let mut buf = t.input.as_bytes().to_vec();
+            rc4.crypt(&mut buf);
+            assert_eq!(output, buf);
+        }
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert_eq!(Rc4::try_new(b"").unwrap_err(), Rc4KeyError::Empty);
+        assert_eq!(Rc4::try_new(&[0; 257]).unwrap_err(), Rc4KeyError::TooLong(257));
+        assert!(Rc4::try_new(&[0; 1]).is_ok());
+        assert!(Rc4::try_new(&[0; 256]).is_ok());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_new_empty_key() {
+        let _ = Rc4::new(b"");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_new_long_key() {
+        let _ = Rc4::new(&[0; 257]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_crypt_to_length_mismatch() {
+        let mut rc4 = Rc4::new(b"Key");
+        rc4.crypt_to(b"Plaintext", &mut [0; 8]);
+    }
+
+    #[test]
+    fn test_crypt_rfc6229() {
+        // RFC 6229, keystream of 16 bytes at offsets 0, 16, and 240
+        #[rustfmt::skip]
+        let tests: &[(&[u8], [[u8; 16]; 3])] = &[
+            (
+                &[0x01, 0x02, 0x03, 0x04, 0x05],
+                [
+                    [0xb2, 0x39, 0x63, 0x05, 0xf0, 0x3d, 0xc0, 0x27, 0xcc, 0xc3, 0x52, 0x4a, 0x0a, 0x11, 0x18, 0xa8],
+                    [0x69, 0x82, 0x94, 0x4f, 0x18, 0xfc, 0x82, 0xd5, 0x89, 0xc4, 0x03, 0xa4, 0x7a, 0x0d, 0x09, 0x19],
+                    [0x28, 0xcb, 0x11, 0x32, 0xc9, 0x6c, 0xe2, 0x86, 0x42, 0x1d, 0xca, 0xad, 0xb8, 0xb6, 0x9e, 0xae],
+                ],
+            ),
+            (
+                &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07],
+                [
+                    [0x29, 0x3f, 0x02, 0xd4, 0x7f, 0x37, 0xc9, 0xb6, 0x33, 0xf2, 0xaf, 0x52, 0x85, 0xfe, 0xb4, 0x6b],
+                    [0xe6, 0x20, 0xf1, 0x39, 0x0d, 0x19, 0xbd, 0x84, 0xe2, 0xe0, 0xfd, 0x75, 0x20, 0x31, 0xaf, 0xc1],
+                    [0x91, 0x4f, 0x02, 0x53, 0x1c, 0x92, 0x18, 0x81, 0x0d, 0xf6, 0x0f, 0x67, 0xe3, 0x38, 0x15, 0x4c],
+                ],
+            ),
+            (
+                &[
+                    0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+                    0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10,
+                ],
+                [
+                    [0x9a, 0xc7, 0xcc, 0x9a, 0x60, 0x9d, 0x1e, 0xf7, 0xb2, 0x93, 0x28, 0x99, 0xcd, 0xe4, 0x1b, 0x97],
+                    [0x52, 0x48, 0xc4, 0x95, 0x90, 0x14, 0x12, 0x6a, 0x6e, 0x8a, 0x84, 0xf1, 0x1d, 0x1a, 0x9e, 0x1c],
+                    [0x06, 0x59, 0x02, 0xe4, 0xb6, 0x20, 0xf6, 0xcc, 0x36, 0xc8, 0x58, 0x9f, 0x66, 0x43, 0x2f, 0x2b],
+                ],
+            ),
+        ];
+        for (key, output) in tests {
+            let mut rc4 = Rc4::new(key);
+            let mut buf = [0; 256];
+            rc4.crypt(&mut buf);
+            for (offset, expected) in [0, 16, 240].iter().zip(output) {
+                assert_eq!(buf[*offset..*offset + 16], *expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_new_with_drop() {
+        // RFC 6229, 40-bit key, keystream at offset 256
+        let key = [0x01, 0x02, 0x03, 0x04, 0x05];
+        let output = [
+            0x1c, 0xfc, 0xf6, 0x2b, 0x03, 0xed, 0xdb, 0x64, 0x1d, 0x77, 0xdf, 0xcf, 0x7f, 0x8d, 0x8c, 0x93,
+        ];
+        let mut rc4 = Rc4::new_with_drop(&key, 256);
+        let mut buf = [0; 16];
+        rc4.crypt(&mut buf);
+        assert_eq!(buf, output);
+
+        let mut rc4 = Rc4::new_with_drop(&key, 0);
+        let mut buf = [0; 16];
+        rc4.crypt(&mut buf);
+        assert_eq!(buf[..4], [0xb2, 0x39, 0x63, 0x05]);
+    }
 }